@@ -47,6 +47,7 @@
                 trunk
                 pkg-config
                 cargo-nextest
+                rgbds
                 ;
             }
             ++ [rust-pkg]
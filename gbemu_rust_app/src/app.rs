@@ -1,10 +1,12 @@
 #![allow(unused_variables)]
 
 mod action;
+mod debug;
 mod input;
 mod stats;
 mod task;
 
+use debug::{DebugCommand, DebugWindows};
 use egui::Vec2;
 use gbemu_rust_lib::prelude::LCD_HEIGHT;
 use gbemu_rust_lib::prelude::LCD_WIDTH;
@@ -13,6 +15,11 @@ use input::InputHandler;
 use stats::Stats;
 
 use gbemu_rust_lib::prelude::Emulator;
+use gbemu_rust_lib::prelude::Model;
+use gbemu_rust_lib::prelude::Palette;
+use gbemu_rust_lib::prelude::RunTarget;
+use gbemu_rust_lib::prelude::StopReason;
+use gbemu_rust_lib::prelude::TraceFormat;
 
 use poll_promise::Promise;
 use rfd::AsyncFileDialog;
@@ -22,6 +29,12 @@ use std::fmt::Display;
 const MIN_FPS: f32 = 10.0;
 const TEXTURE_SIZE: [usize; 2] = [LCD_WIDTH, LCD_HEIGHT];
 const CYCLES_PER_SECOND: u32 = 4_194_304;
+const SCREENSHOT_SCALE: usize = 1;
+// upper limit for a single step command, stepping out of a function which never returns should
+// not freeze the app. The debugger counts M-cycles, so this is one second of emulated time.
+const STEP_CYCLES: u64 = CYCLES_PER_SECOND as u64 / 4;
+// instructions kept while recording a trace
+const TRACE_LENGTH: usize = 10_000;
 const DEFAULT_PALETTE: [egui::Color32; 4] = [
     egui::Color32::from_rgba_premultiplied(0xe0, 0xf0, 0xe7, 0xff), // White
     egui::Color32::from_rgba_premultiplied(0x8b, 0xa3, 0x94, 0xff), // Light gray
@@ -55,6 +68,12 @@ pub struct GbemuApp {
 
     state: AppState,
     emulator: Option<Emulator>,
+    model: Model,
+    boot_rom: Option<Vec<u8>>,
+    error: Option<String>,
+    screenshot_task: Option<Promise<Option<String>>>,
+    trace_task: Option<Promise<Option<String>>>,
+    debug: DebugWindows,
 
     texture: egui::TextureHandle,
 }
@@ -63,6 +82,8 @@ impl GbemuApp {
     pub fn new<'a>(
         cc: &'a &eframe::CreationContext<'a>,
         emulator: Option<Emulator>,
+        model: Model,
+        boot_rom: Option<Vec<u8>>,
     ) -> Option<Self> {
         Some(Self {
             scale: 1.0,
@@ -74,6 +95,12 @@ impl GbemuApp {
                 AppState::Running
             },
             emulator,
+            model,
+            boot_rom,
+            error: None,
+            screenshot_task: None,
+            trace_task: None,
+            debug: DebugWindows::default(),
             texture: cc.egui_ctx.load_texture(
                 "gbemu",
                 egui::ColorImage::new(TEXTURE_SIZE, DEFAULT_PALETTE[0]),
@@ -81,6 +108,139 @@ impl GbemuApp {
             ),
         })
     }
+
+    pub fn show_error(&mut self, message: String) {
+        self.error = Some(message);
+    }
+
+    fn save_screenshot(&mut self, ctx: &egui::Context) {
+        // only one save dialog at a time
+        let Some(emulator) = self
+            .emulator
+            .as_ref()
+            .filter(|_| self.screenshot_task.is_none())
+        else {
+            return;
+        };
+
+        let palette: Palette = DEFAULT_PALETTE.map(|color| [color.r(), color.g(), color.b()]);
+        let png = match emulator.screenshot().to_png(&palette, SCREENSHOT_SCALE) {
+            Ok(png) => png,
+            Err(err) => {
+                self.error = Some(format!("Could not encode the screenshot: {}", err));
+                return;
+            },
+        };
+
+        let ctx_clone = ctx.clone();
+        self.screenshot_task = Some(task::execute(async move {
+            let result = match AsyncFileDialog::new()
+                .add_filter("PNG", &["png"])
+                .set_file_name("screenshot.png")
+                .save_file()
+                .await
+            {
+                Some(file) => file.write(&png).await.err().map(|err| err.to_string()),
+                None => None,
+            };
+            ctx_clone.request_repaint();
+
+            result
+        }));
+    }
+
+    fn save_trace(&mut self, ctx: &egui::Context, format: TraceFormat) {
+        // only one save dialog at a time
+        let Some(emulator) = self.emulator.as_ref().filter(|_| self.trace_task.is_none()) else {
+            return;
+        };
+
+        let mut trace = Vec::new();
+        if let Err(err) = emulator
+            .trace
+            .export(&mut trace, format, &emulator.debugger.symbols)
+        {
+            self.error = Some(format!("Could not export the trace: {}", err));
+            return;
+        }
+
+        let file_name = match format {
+            TraceFormat::JsonLines => "trace.jsonl",
+            _ => "trace.log",
+        };
+        let ctx_clone = ctx.clone();
+        self.trace_task = Some(task::execute(async move {
+            let result = match AsyncFileDialog::new()
+                .set_file_name(file_name)
+                .save_file()
+                .await
+            {
+                Some(file) => file.write(&trace).await.err().map(|err| err.to_string()),
+                None => None,
+            };
+            ctx_clone.request_repaint();
+
+            result
+        }));
+    }
+
+    fn trace_menu_ui(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        let mut recording = self
+            .emulator
+            .as_ref()
+            .is_some_and(|emulator| emulator.trace.is_enabled());
+        if ui
+            .add_enabled(
+                self.emulator.is_some(),
+                egui::Checkbox::new(&mut recording, "Record trace"),
+            )
+            .changed()
+        {
+            let trace = &mut self.emulator.as_mut().unwrap().trace;
+            if recording {
+                trace.enable(TRACE_LENGTH);
+            } else {
+                trace.disable();
+            }
+        }
+
+        ui.add_enabled_ui(recording && self.trace_task.is_none(), |ui| {
+            ui.menu_button("Export trace", |ui| {
+                for format in TraceFormat::ALL {
+                    if ui.button(format.to_string()).clicked() {
+                        self.save_trace(ctx, format);
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
+    }
+
+    fn debug_command(&mut self, command: DebugCommand) {
+        let Some(emulator) = self.emulator.as_mut() else {
+            return;
+        };
+
+        let result = match command {
+            DebugCommand::Pause => {
+                self.state = AppState::Paused;
+                return;
+            },
+            DebugCommand::Continue => {
+                self.debug.last_stop = None;
+                self.state = AppState::Running;
+                return;
+            },
+            DebugCommand::StepInto => emulator.step_into(STEP_CYCLES),
+            DebugCommand::StepOver => emulator.step_over(STEP_CYCLES),
+            DebugCommand::StepOut => emulator.step_out(STEP_CYCLES),
+        };
+
+        match result {
+            Ok(reason) => self.debug.last_stop = Some(reason),
+            Err(err) => self.error = Some(format!("Execution error: {:?}", err)),
+        }
+    }
 }
 
 impl eframe::App for GbemuApp {
@@ -90,9 +250,24 @@ impl eframe::App for GbemuApp {
             AppState::FileDialog(promise) => {
                 if let Some(rom_file) = promise.ready() {
                     if let Some(rom) = rom_file {
-                        self.emulator =
-                            Some(Emulator::new_from_buffer(rom.clone(), true, None, None).unwrap());
-                        self.state = AppState::Running;
+                        match Emulator::new_from_buffer_with_model(
+                            rom.clone(),
+                            self.model,
+                            self.boot_rom.clone(),
+                            true,
+                            None,
+                            None,
+                        ) {
+                            Ok(emulator) => {
+                                self.emulator = Some(emulator);
+                                self.state = AppState::Running;
+                            },
+                            Err(err) => {
+                                log::error!("{}", err);
+                                self.error = Some(format!("Could not load the ROM: {}", err));
+                                self.state = AppState::Idle;
+                            },
+                        }
                     } else {
                         self.state = AppState::Idle;
                     }
@@ -125,6 +300,9 @@ impl eframe::App for GbemuApp {
                                     action::Action::TogglePause => {
                                         self.state = AppState::Paused;
                                     },
+                                    action::Action::SaveScreenshot => {
+                                        self.save_screenshot(ctx);
+                                    },
                                     action::Action::KeyEvent { key, pressed } => {
                                         self.emulator
                                             .as_mut()
@@ -141,8 +319,26 @@ impl eframe::App for GbemuApp {
                     }
                 }
 
-                for _ in 0..cycles {
-                    let _ = self.emulator.as_mut().unwrap().step();
+                // only the most recent stop is of interest
+                self.debug.last_stop = None;
+
+                let emulator = self.emulator.as_mut().unwrap();
+                if emulator.debugger.is_empty() {
+                    for _ in 0..cycles {
+                        let _ = emulator.step();
+                    }
+                } else {
+                    match emulator.run_until(RunTarget::Breakpoint, cycles as u64) {
+                        Ok(StopReason::CycleLimit) => {},
+                        Ok(reason) => {
+                            self.debug.last_stop = Some(reason);
+                            self.state = AppState::Paused;
+                        },
+                        Err(err) => {
+                            self.error = Some(format!("Execution error: {:?}", err));
+                            self.state = AppState::Paused;
+                        },
+                    }
                 }
 
                 ctx.request_repaint();
@@ -162,6 +358,9 @@ impl eframe::App for GbemuApp {
                             Some(action::Action::TogglePause) => {
                                 self.state = AppState::Running;
                             },
+                            Some(action::Action::SaveScreenshot) => {
+                                self.save_screenshot(ctx);
+                            },
                             _ => {},
                         },
                         _ => {},
@@ -218,6 +417,19 @@ impl eframe::App for GbemuApp {
                         }
                     }
 
+                    if ui
+                        .add_enabled(
+                            (matches!(self.state, AppState::Running)
+                                || matches!(self.state, AppState::Paused))
+                                && self.screenshot_task.is_none(),
+                            egui::Button::new("Save screenshot").shortcut_text("F12"),
+                        )
+                        .clicked()
+                    {
+                        self.save_screenshot(ctx);
+                        ui.close_menu();
+                    }
+
                     if ui
                         .add_enabled(
                             matches!(self.state, AppState::Running)
@@ -249,12 +461,57 @@ impl eframe::App for GbemuApp {
                     }
                 });
 
+                ui.menu_button("Debug", |ui| {
+                    self.debug.menu_ui(ui);
+                    ui.separator();
+                    self.trace_menu_ui(ctx, ui);
+                });
+
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Min), |ui| {
                     egui::widgets::global_theme_preference_buttons(ui)
                 });
             });
         });
 
+        if let Some(result) = self.screenshot_task.as_ref().and_then(|task| task.ready()) {
+            if let Some(err) = result {
+                self.error = Some(format!("Could not save the screenshot: {}", err));
+            }
+            self.screenshot_task = None;
+        }
+
+        if let Some(result) = self.trace_task.as_ref().and_then(|task| task.ready()) {
+            if let Some(err) = result {
+                self.error = Some(format!("Could not save the trace: {}", err));
+            }
+            self.trace_task = None;
+        }
+
+        if let Some(error) = &self.error {
+            let mut open = true;
+            egui::Window::new("Error")
+                .collapsible(false)
+                .resizable(false)
+                .anchor(egui::Align2::CENTER_CENTER, Vec2::ZERO)
+                .show(ctx, |ui| {
+                    ui.label(error);
+                    if ui.button("OK").clicked() {
+                        open = false;
+                    }
+                });
+
+            if !open {
+                self.error = None;
+            }
+        }
+
+        if let Some(emulator) = self.emulator.as_mut() {
+            let paused = matches!(self.state, AppState::Paused);
+            if let Some(command) = self.debug.show(ctx, emulator, paused) {
+                self.debug_command(command);
+            }
+        }
+
         egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.with_layout(egui::Layout::left_to_right(egui::Align::Min), |ui| {
@@ -2,5 +2,6 @@ use gbemu_rust_lib::prelude::Key;
 
 pub enum Action {
     TogglePause,
+    SaveScreenshot,
     KeyEvent { key: Key, pressed: bool },
 }
@@ -0,0 +1,104 @@
+mod cpu;
+mod disassembly;
+mod interrupts;
+mod io_registers;
+mod memory;
+mod objects;
+mod tile_maps;
+mod tiles;
+
+use gbemu_rust_lib::prelude::{Emulator, Screenshot, StopReason};
+
+use cpu::CpuWindow;
+use disassembly::DisassemblyWindow;
+use interrupts::InterruptsWindow;
+use io_registers::IoRegistersWindow;
+use memory::MemoryWindow;
+use objects::ObjectsWindow;
+use tile_maps::TileMapsWindow;
+use tiles::TilesWindow;
+
+use super::DEFAULT_PALETTE;
+
+pub enum DebugCommand {
+    Pause,
+    Continue,
+    StepInto,
+    StepOver,
+    StepOut,
+}
+
+// Uploads an image of the emulator into the texture, which is created on first use
+fn update_texture(
+    ctx: &egui::Context,
+    texture: &mut Option<egui::TextureHandle>,
+    name: &str,
+    image: &Screenshot,
+) -> egui::TextureHandle {
+    let image = egui::ColorImage {
+        size: [image.width, image.height],
+        pixels: image
+            .pixels
+            .iter()
+            .map(|pixel| DEFAULT_PALETTE[u8::from(*pixel) as usize])
+            .collect(),
+    };
+
+    match texture {
+        Some(texture) => {
+            texture.set(image, egui::TextureOptions::NEAREST);
+            texture.clone()
+        },
+        None => texture
+            .insert(ctx.load_texture(name, image, egui::TextureOptions::NEAREST))
+            .clone(),
+    }
+}
+
+#[derive(Default)]
+pub struct DebugWindows {
+    cpu: CpuWindow,
+    disassembly: DisassemblyWindow,
+    interrupts: InterruptsWindow,
+    memory: MemoryWindow,
+    tiles: TilesWindow,
+    tile_maps: TileMapsWindow,
+    objects: ObjectsWindow,
+    io_registers: IoRegistersWindow,
+
+    // why the emulator was paused the last time
+    pub last_stop: Option<StopReason>,
+}
+
+impl DebugWindows {
+    pub fn menu_ui(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.cpu.open, "CPU");
+        ui.checkbox(&mut self.disassembly.open, "Disassembly");
+        ui.checkbox(&mut self.interrupts.open, "Interrupts");
+        ui.checkbox(&mut self.memory.open, "Memory");
+        ui.checkbox(&mut self.tiles.open, "Tile data");
+        ui.checkbox(&mut self.tile_maps.open, "Tile maps");
+        ui.checkbox(&mut self.objects.open, "Objects");
+        ui.checkbox(&mut self.io_registers.open, "I/O registers");
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        emulator: &mut Emulator,
+        paused: bool,
+    ) -> Option<DebugCommand> {
+        let command = self
+            .cpu
+            .show(ctx, emulator, paused, self.last_stop.as_ref());
+        self.disassembly.show(ctx, emulator);
+        self.interrupts.show(ctx, emulator, paused);
+        self.memory.show(ctx, emulator);
+        self.tiles.show(ctx, emulator);
+        self.tile_maps.show(ctx, emulator);
+        self.objects.show(ctx, emulator);
+        self.io_registers.show(ctx, emulator);
+
+        command
+    }
+}
@@ -0,0 +1,115 @@
+use gbemu_rust_lib::prelude::{Emulator, StopReason};
+
+use super::DebugCommand;
+
+#[derive(Default)]
+pub struct CpuWindow {
+    pub open: bool,
+}
+
+impl CpuWindow {
+    fn registers_ui(ui: &mut egui::Ui, emulator: &Emulator) {
+        let registers = &emulator.cpu.registers;
+        let pc = match emulator.cpu.instruction_address() {
+            Some(address) => format!("{:04X}", address),
+            None => "ISR".to_owned(),
+        };
+
+        egui::Grid::new("cpu_registers")
+            .num_columns(4)
+            .striped(true)
+            .show(ui, |ui| {
+                ui.label("AF");
+                ui.monospace(format!("{:04X}", registers.get_af()));
+                ui.label("BC");
+                ui.monospace(format!("{:04X}", registers.get_bc()));
+                ui.end_row();
+
+                ui.label("DE");
+                ui.monospace(format!("{:04X}", registers.get_de()));
+                ui.label("HL");
+                ui.monospace(format!("{:04X}", registers.get_hl()));
+                ui.end_row();
+
+                ui.label("SP");
+                ui.monospace(format!("{:04X}", registers.sp));
+                ui.label("PC");
+                ui.monospace(pc);
+                ui.end_row();
+            });
+
+        ui.horizontal(|ui| {
+            let flags = [
+                ("Z", registers.get_flag_zero()),
+                ("N", registers.get_flag_subtraction()),
+                ("H", registers.get_flag_half_carry()),
+                ("C", registers.get_flag_carry()),
+            ];
+            for (name, mut set) in flags {
+                ui.add_enabled(false, egui::Checkbox::new(&mut set, name));
+            }
+        });
+
+        ui.horizontal(|ui| {
+            let mut ime = emulator.cpu.interrupt_enabled;
+            let mut halted = emulator.cpu.halted;
+            ui.add_enabled(false, egui::Checkbox::new(&mut ime, "IME"));
+            if emulator.cpu.interrupt_enable_pending {
+                ui.weak("(pending)");
+            }
+            ui.add_enabled(false, egui::Checkbox::new(&mut halted, "Halted"));
+        });
+    }
+
+    fn controls_ui(ui: &mut egui::Ui, paused: bool) -> Option<DebugCommand> {
+        let mut command = None;
+
+        ui.horizontal(|ui| {
+            if paused {
+                if ui.button("Continue").clicked() {
+                    command = Some(DebugCommand::Continue);
+                }
+            } else if ui.button("Pause").clicked() {
+                command = Some(DebugCommand::Pause);
+            }
+
+            let steps = [
+                ("Step into", DebugCommand::StepInto),
+                ("Step over", DebugCommand::StepOver),
+                ("Step out", DebugCommand::StepOut),
+            ];
+            for (label, step) in steps {
+                if ui.add_enabled(paused, egui::Button::new(label)).clicked() {
+                    command = Some(step);
+                }
+            }
+        });
+
+        command
+    }
+
+    pub fn show(
+        &mut self,
+        ctx: &egui::Context,
+        emulator: &Emulator,
+        paused: bool,
+        last_stop: Option<&StopReason>,
+    ) -> Option<DebugCommand> {
+        let mut command = None;
+
+        egui::Window::new("CPU")
+            .open(&mut self.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                Self::registers_ui(ui, emulator);
+                ui.separator();
+
+                command = Self::controls_ui(ui, paused);
+                if let Some(reason) = last_stop.filter(|_| paused) {
+                    ui.weak(format!("Stopped: {:?}", reason));
+                }
+            });
+
+        command
+    }
+}
@@ -0,0 +1,177 @@
+use gbemu_rust_lib::prelude::{
+    Breakpoint, Bus, DisassembledInstruction, Emulator, Symbols, disassemble_range,
+};
+
+const LINES: usize = 32;
+// instructions shown before the PC when the view jumps
+const CONTEXT_BYTES: u16 = 12;
+// the view follows the PC once it gets this close to the end
+const FOLLOW_MARGIN: usize = 8;
+
+pub struct DisassemblyWindow {
+    pub open: bool,
+    start: u16,
+    follow_pc: bool,
+    goto: String,
+}
+
+impl Default for DisassemblyWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            start: 0x0100,
+            follow_pc: true,
+            goto: String::new(),
+        }
+    }
+}
+
+// Finds an address before the PC from which a linear sweep ends up exactly at the PC, so the
+// view shows a few instructions of context
+fn anchor(emulator: &Emulator, pc: u16, symbols: &Symbols) -> u16 {
+    (1..=CONTEXT_BYTES)
+        .rev()
+        .map(|offset| pc.wrapping_sub(offset))
+        .find(|start| {
+            disassemble_range(&emulator.system, *start, CONTEXT_BYTES as usize, symbols)
+                .iter()
+                .any(|instruction| instruction.address == pc)
+        })
+        .unwrap_or(pc)
+}
+
+fn breakpoint_at(emulator: &Emulator, address: u16) -> Option<usize> {
+    let bank = emulator.system.bank(address);
+
+    emulator
+        .debugger
+        .breakpoints()
+        .find(|(_, breakpoint)| {
+            breakpoint.address == address && (breakpoint.bank.is_none() || breakpoint.bank == bank)
+        })
+        .map(|(id, _)| id)
+}
+
+fn toggle_breakpoint(emulator: &mut Emulator, address: u16) {
+    if let Some(id) = breakpoint_at(emulator, address) {
+        emulator.debugger.remove_breakpoint(id);
+        return;
+    }
+
+    let mut breakpoint = Breakpoint::new(address);
+    if let Some(bank) = emulator.system.bank(address) {
+        breakpoint = breakpoint.in_bank(bank);
+    }
+    emulator.debugger.add_breakpoint(breakpoint);
+}
+
+impl DisassemblyWindow {
+    fn lines(&mut self, emulator: &Emulator) -> Vec<DisassembledInstruction> {
+        let symbols = &emulator.debugger.symbols;
+        let pc = emulator.cpu.instruction_address();
+
+        let lines = disassemble_range(&emulator.system, self.start, LINES, symbols);
+        match pc.filter(|_| self.follow_pc) {
+            Some(pc)
+                if !lines[..LINES - FOLLOW_MARGIN]
+                    .iter()
+                    .any(|instruction| instruction.address == pc) =>
+            {
+                self.start = anchor(emulator, pc, symbols);
+                disassemble_range(&emulator.system, self.start, LINES, symbols)
+            },
+            _ => lines,
+        }
+    }
+
+    fn toolbar_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.follow_pc, "Follow PC");
+
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.goto)
+                    .hint_text("Go to address")
+                    .desired_width(100.0),
+            );
+            if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                let address = self.goto.trim().trim_start_matches(['$', '#']);
+                if let Ok(address) = u16::from_str_radix(address, 16) {
+                    self.start = address;
+                    self.follow_pc = false;
+                }
+            }
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, emulator: &mut Emulator) {
+        let mut open = self.open;
+        let mut toggle = None;
+
+        egui::Window::new("Disassembly")
+            .open(&mut open)
+            .default_width(320.0)
+            .show(ctx, |ui| {
+                self.toolbar_ui(ui);
+                ui.separator();
+
+                let pc = emulator.cpu.instruction_address();
+                let lines = self.lines(emulator);
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("disassembly")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for line in &lines {
+                                if let Some(label) = &line.label {
+                                    ui.label("");
+                                    ui.strong(format!("{}:", label));
+                                    ui.end_row();
+                                }
+
+                                // clicking the gutter toggles a breakpoint
+                                let marker = if breakpoint_at(emulator, line.address).is_some() {
+                                    egui::RichText::new("●").color(egui::Color32::RED)
+                                } else {
+                                    egui::RichText::new("○").weak()
+                                };
+                                if ui
+                                    .add(egui::Label::new(marker).sense(egui::Sense::click()))
+                                    .on_hover_text("Toggle breakpoint")
+                                    .clicked()
+                                {
+                                    toggle = Some(line.address);
+                                }
+
+                                let current = pc == Some(line.address);
+                                let address = format!(
+                                    "{} {:04X}",
+                                    if current { "▶" } else { " " },
+                                    line.address
+                                );
+                                let bytes = line
+                                    .bytes
+                                    .iter()
+                                    .map(|byte| format!("{:02X}", byte))
+                                    .collect::<Vec<_>>()
+                                    .join(" ");
+
+                                if current {
+                                    ui.label(egui::RichText::new(address).monospace().strong());
+                                } else {
+                                    ui.monospace(address);
+                                }
+                                ui.monospace(bytes);
+                                ui.monospace(&line.text);
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.open = open;
+        if let Some(address) = toggle {
+            toggle_breakpoint(emulator, address);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use gbemu_rust_lib::prelude::{Bus, Emulator, IE_REGISTER_ADDR, Interrupt};
+
+// in the order of their bits and priority
+const INTERRUPTS: [(Interrupt, &str); 5] = [
+    (Interrupt::VBlank, "VBlank"),
+    (Interrupt::Lcd, "LCD"),
+    (Interrupt::Timer, "Timer"),
+    (Interrupt::Serial, "Serial"),
+    (Interrupt::Joypad, "Joypad"),
+];
+
+#[derive(Default)]
+pub struct InterruptsWindow {
+    pub open: bool,
+}
+
+impl InterruptsWindow {
+    // the bits can be toggled while the emulator is paused
+    pub fn show(&mut self, ctx: &egui::Context, emulator: &mut Emulator, paused: bool) {
+        egui::Window::new("Interrupts")
+            .open(&mut self.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let mut enable = emulator.system.interrupt_enable();
+                let mut flags = emulator.system.interrupt_flags();
+
+                egui::Grid::new("interrupts")
+                    .num_columns(4)
+                    .striped(true)
+                    .show(ui, |ui| {
+                        ui.strong("Interrupt");
+                        ui.strong("Vector");
+                        ui.strong("IE");
+                        ui.strong("IF");
+                        ui.end_row();
+
+                        for (bit, (interrupt, name)) in INTERRUPTS.into_iter().enumerate() {
+                            ui.label(name);
+                            ui.monospace(format!("{:04X}", u16::from(interrupt)));
+
+                            for register in [&mut enable, &mut flags] {
+                                let mut set = *register & (1 << bit) != 0;
+                                if ui
+                                    .add_enabled(paused, egui::Checkbox::without_text(&mut set))
+                                    .changed()
+                                {
+                                    *register ^= 1 << bit;
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("IE: {:02X}", enable));
+                    ui.monospace(format!("IF: {:02X}", flags));
+                });
+
+                if enable != emulator.system.interrupt_enable() {
+                    emulator.system.write_byte(IE_REGISTER_ADDR, enable);
+                }
+                if flags != emulator.system.interrupt_flags() {
+                    emulator.system.set_interrupt_flags(flags);
+                }
+            });
+    }
+}
@@ -0,0 +1,95 @@
+use gbemu_rust_lib::prelude::{
+    Emulator, H_RAM_ADDR, IE_REGISTER_ADDR, IO_REGISTERS_ADDR, decode_io_register, io_register_name,
+};
+
+#[derive(Default)]
+pub struct IoRegistersWindow {
+    pub open: bool,
+    show_unnamed: bool,
+    selected: Option<u16>,
+    edit: String,
+}
+
+impl IoRegistersWindow {
+    // returns the value which should be written
+    fn value_ui(&mut self, ui: &mut egui::Ui, address: u16, value: u8) -> Option<u8> {
+        if self.selected == Some(address) {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.edit)
+                    .font(egui::TextStyle::Monospace)
+                    .char_limit(2)
+                    .desired_width(14.0),
+            );
+            if response.lost_focus() {
+                self.selected = None;
+                if ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    return u8::from_str_radix(self.edit.trim(), 16).ok();
+                }
+            }
+            return None;
+        }
+
+        let text = egui::RichText::new(format!("{:02X}", value)).monospace();
+        if ui
+            .add(egui::Label::new(text).sense(egui::Sense::click()))
+            .on_hover_text("Click to edit")
+            .clicked()
+        {
+            self.selected = Some(address);
+            self.edit = format!("{:02X}", value);
+        }
+
+        None
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, emulator: &mut Emulator) {
+        let mut open = self.open;
+        let mut write = None;
+
+        egui::Window::new("I/O registers")
+            .open(&mut open)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                ui.checkbox(&mut self.show_unnamed, "Show unnamed registers");
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("io_registers")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for address in (IO_REGISTERS_ADDR..H_RAM_ADDR).chain([IE_REGISTER_ADDR])
+                            {
+                                let name = io_register_name(address);
+                                if name.is_none() && !self.show_unnamed {
+                                    continue;
+                                }
+                                let value = emulator.system.read_byte(address);
+
+                                ui.monospace(format!("{:04X}", address));
+                                ui.label(name.unwrap_or("-"));
+                                if let Some(value) = self.value_ui(ui, address, value) {
+                                    write = Some((address, value));
+                                }
+
+                                let fields = decode_io_register(address, value)
+                                    .iter()
+                                    .map(|field| field.to_string())
+                                    .collect::<Vec<_>>()
+                                    .join(", ");
+                                ui.add(
+                                    egui::Label::new(egui::RichText::new(fields).small()).wrap(),
+                                );
+                                ui.end_row();
+                            }
+                        });
+                });
+            });
+
+        self.open = open;
+        // writes go through the bus, so they have the same side effects as on hardware
+        if let Some((address, value)) = write {
+            emulator.system.write_byte(address, value);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use gbemu_rust_lib::prelude::{Emulator, MemoryRegion};
+
+const ADDRESS_SPACE: usize = 0x10000;
+const BYTES_PER_ROW: usize = 16;
+const ROWS: usize = ADDRESS_SPACE / BYTES_PER_ROW;
+
+const CHANGED_COLOR: egui::Color32 = egui::Color32::from_rgb(0xE0, 0x60, 0x40);
+
+// `DE AD ?? EF` with `??` as wildcard, or a quoted ASCII string
+fn parse_pattern(text: &str) -> Option<Vec<Option<u8>>> {
+    let text = text.trim();
+    if let Some(string) = text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    {
+        return Some(string.bytes().map(Some).collect());
+    }
+
+    let pattern = text
+        .split_whitespace()
+        .map(|byte| match byte {
+            "??" => Some(None),
+            _ => u8::from_str_radix(byte, 16).ok().map(Some),
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    (!pattern.is_empty()).then_some(pattern)
+}
+
+fn find(memory: &[u8], pattern: &[Option<u8>], from: usize) -> Option<usize> {
+    // search wraps around at the end of the address space
+    (0..memory.len())
+        .map(|offset| (from + offset) % memory.len())
+        .find(|start| {
+            pattern.iter().enumerate().all(|(index, byte)| {
+                byte.is_none_or(|byte| memory[(start + index) % memory.len()] == byte)
+            })
+        })
+}
+
+fn printable(byte: u8) -> char {
+    if byte.is_ascii_graphic() || byte == b' ' {
+        byte as char
+    } else {
+        '.'
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryWindow {
+    pub open: bool,
+    // memory as of the previous emulated frame, to highlight changes
+    previous: Vec<u8>,
+    // memory and cycle count of the emulator when the window was last drawn
+    shown: Vec<u8>,
+    shown_cycle: u64,
+    selected: Option<u16>,
+    edit: String,
+    search: String,
+    search_error: bool,
+    scroll_to: Option<u16>,
+}
+
+impl MemoryWindow {
+    fn select(&mut self, address: u16, memory: &[u8]) {
+        self.selected = Some(address);
+        self.edit = format!("{:02X}", memory[address as usize]);
+    }
+
+    fn banks_ui(ui: &mut egui::Ui, emulator: &Emulator) {
+        let system = &emulator.system;
+
+        ui.horizontal(|ui| {
+            ui.monospace(format!("ROMX: {:02X}", system.rom_bank(0x4000)));
+            ui.separator();
+            ui.monospace(format!("SRAM: {:02X}", system.ram_bank()));
+            ui.separator();
+            ui.monospace(format!("WRAMX: {:02X}", system.w_ram_bank()));
+        });
+    }
+
+    fn search_ui(&mut self, ui: &mut egui::Ui, memory: &[u8]) {
+        ui.horizontal(|ui| {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.search)
+                    .hint_text("DE AD ?? EF or \"text\"")
+                    .desired_width(160.0)
+                    .text_color_opt(self.search_error.then_some(egui::Color32::RED)),
+            );
+            let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+
+            if ui.button("Find next").clicked() || submitted {
+                let from = self.selected.map_or(0, |address| address as usize + 1);
+                let result =
+                    parse_pattern(&self.search).and_then(|pattern| find(memory, &pattern, from));
+
+                self.search_error = result.is_none();
+                if let Some(address) = result {
+                    self.select(address as u16, memory);
+                    self.scroll_to = Some(address as u16);
+                }
+            }
+        });
+    }
+
+    // returns the byte which should be written
+    fn row_ui(&mut self, ui: &mut egui::Ui, row: usize, memory: &[u8]) -> Option<(u16, u8)> {
+        let mut write = None;
+        let start = row * BYTES_PER_ROW;
+        let region = MemoryRegion::from_address(start as u16);
+
+        ui.horizontal(|ui| {
+            ui.spacing_mut().item_spacing.x = 4.0;
+            ui.monospace(format!("{:>6}:{:04X}", region.name(), start));
+
+            for address in start..start + BYTES_PER_ROW {
+                let value = memory[address];
+
+                if self.selected == Some(address as u16) {
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut self.edit)
+                            .font(egui::TextStyle::Monospace)
+                            .char_limit(2)
+                            .desired_width(14.0),
+                    );
+                    if response.lost_focus()
+                        && ui.input(|i| i.key_pressed(egui::Key::Enter))
+                        && let Ok(value) = u8::from_str_radix(self.edit.trim(), 16)
+                    {
+                        write = Some((address as u16, value));
+                    }
+                    continue;
+                }
+
+                let mut text = egui::RichText::new(format!("{:02X}", value)).monospace();
+                if self
+                    .previous
+                    .get(address)
+                    .is_some_and(|previous| *previous != value)
+                {
+                    text = text.color(CHANGED_COLOR);
+                }
+                if ui
+                    .add(egui::Label::new(text).sense(egui::Sense::click()))
+                    .clicked()
+                {
+                    self.select(address as u16, memory);
+                }
+            }
+
+            let ascii: String = memory[start..start + BYTES_PER_ROW]
+                .iter()
+                .map(|byte| printable(*byte))
+                .collect();
+            ui.monospace(ascii);
+        });
+
+        write
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, emulator: &mut Emulator) {
+        if !self.open {
+            self.previous.clear();
+            self.shown.clear();
+            return;
+        }
+
+        // the window is redrawn more often than the emulator advances, e.g. while paused
+        let cycle = emulator.trace.cycles();
+        if cycle != self.shown_cycle {
+            self.previous = std::mem::take(&mut self.shown);
+            self.shown_cycle = cycle;
+        }
+
+        let memory: Vec<u8> = (0..ADDRESS_SPACE)
+            .map(|address| emulator.system.read_byte(address as u16))
+            .collect();
+        let mut open = self.open;
+        let mut write = None;
+
+        egui::Window::new("Memory")
+            .open(&mut open)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                Self::banks_ui(ui, emulator);
+                self.search_ui(ui, &memory);
+                ui.separator();
+
+                let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+                let mut scroll_area = egui::ScrollArea::vertical().auto_shrink(false);
+                if let Some(address) = self.scroll_to.take() {
+                    let row = address as usize / BYTES_PER_ROW;
+                    let spacing = ui.spacing().item_spacing.y;
+                    scroll_area =
+                        scroll_area.vertical_scroll_offset(row as f32 * (row_height + spacing));
+                }
+
+                scroll_area.show_rows(ui, row_height, ROWS, |ui, rows| {
+                    for row in rows {
+                        write = write.or(self.row_ui(ui, row, &memory));
+                    }
+                });
+            });
+
+        if let Some((address, value)) = write {
+            emulator.system.write_byte(address, value);
+            // continue with the next byte like a hex editor
+            let next = address.wrapping_add(1);
+            self.select(next, &memory);
+        }
+
+        self.open = open;
+        self.shown = memory;
+    }
+}
@@ -0,0 +1,111 @@
+use gbemu_rust_lib::prelude::{Emulator, NUM_OBJECTS, OAM_ADDR, ObjectInfo};
+
+use super::update_texture;
+
+const PREVIEW_SCALE: f32 = 3.0;
+
+const DROPPED_COLOR: egui::Color32 = egui::Color32::from_rgb(0xE0, 0x60, 0x40);
+
+#[derive(Default)]
+pub struct ObjectsWindow {
+    pub open: bool,
+    only_on_line: bool,
+    textures: Vec<Option<egui::TextureHandle>>,
+}
+
+fn flags_text(info: &ObjectInfo) -> String {
+    let flags = &info.object.sprite_flags;
+    [
+        (flags.background_priority, "P"),
+        (flags.flip_y, "Y"),
+        (flags.flip_x, "X"),
+    ]
+    .iter()
+    .map(|(set, name)| if *set { *name } else { "-" })
+    .chain([if flags.palette { "1" } else { "0" }])
+    .collect()
+}
+
+impl ObjectsWindow {
+    fn row_ui(ui: &mut egui::Ui, index: usize, info: &ObjectInfo, texture: &egui::TextureHandle) {
+        let size = egui::Vec2::new(info.preview.width as f32, info.preview.height as f32);
+        ui.add(egui::Image::new(texture).fit_to_exact_size(size * PREVIEW_SCALE));
+
+        ui.monospace(format!("{:02}", index));
+        ui.monospace(format!("{:04X}", OAM_ADDR as usize + index * 4));
+        ui.monospace(format!(
+            "{:02X},{:02X} ({},{})",
+            info.object.pos_x,
+            info.object.pos_y,
+            info.object.screen_x(),
+            info.object.screen_y()
+        ));
+        ui.monospace(format!("{:02X}", info.object.tile_number));
+        ui.monospace(flags_text(info))
+            .on_hover_text("BG priority, Y flip, X flip, OBP");
+
+        if info.dropped {
+            ui.label(egui::RichText::new("dropped").color(DROPPED_COLOR))
+                .on_hover_text("More than 10 objects on this line");
+        } else if info.on_line {
+            ui.label("visible");
+        } else {
+            ui.weak("-");
+        }
+        ui.end_row();
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, emulator: &Emulator) {
+        if !self.open {
+            return;
+        }
+
+        let graphics = &emulator.system.graphics;
+        let objects = graphics.objects();
+        self.textures.resize(NUM_OBJECTS, None);
+        let textures: Vec<_> = objects
+            .iter()
+            .zip(self.textures.iter_mut())
+            .enumerate()
+            .map(|(index, (info, texture))| {
+                update_texture(ctx, texture, &format!("object_{}", index), &info.preview)
+            })
+            .collect();
+
+        egui::Window::new("Objects")
+            .open(&mut self.open)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.monospace(format!("LY: {:3}", graphics.registers.get_lcd_ly()));
+                    ui.separator();
+                    ui.monospace(format!("Size: 8x{}", graphics.object_height()));
+                    ui.separator();
+                    ui.checkbox(&mut self.only_on_line, "Only current line");
+                });
+                ui.separator();
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("objects")
+                        .num_columns(7)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            for header in ["", "#", "Addr", "X,Y (screen)", "Tile", "Flags", "Line"]
+                            {
+                                ui.strong(header);
+                            }
+                            ui.end_row();
+
+                            for (index, (info, texture)) in
+                                objects.iter().zip(&textures).enumerate()
+                            {
+                                if self.only_on_line && !info.on_line {
+                                    continue;
+                                }
+                                Self::row_ui(ui, index, info, texture);
+                            }
+                        });
+                });
+            });
+    }
+}
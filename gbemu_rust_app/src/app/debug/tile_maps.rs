@@ -0,0 +1,261 @@
+use gbemu_rust_lib::prelude::{
+    Emulator, LCD_HEIGHT, LCD_WIDTH, TILE_MAP_WIDTH, TILE_MAPS_ADDR, V_RAM_ADDR,
+};
+
+use super::update_texture;
+
+const TILE_MAP_BYTES: usize = TILE_MAP_WIDTH * TILE_MAP_WIDTH;
+const TILE_BYTES: usize = 16;
+const MAP_SIZE: usize = TILE_MAP_WIDTH * 8;
+// the window is drawn starting at WX - 7
+const WINDOW_X_OFFSET: usize = 7;
+
+const VIEWPORT_COLOR: egui::Color32 = egui::Color32::from_rgb(0xE0, 0x40, 0x40);
+const WINDOW_COLOR: egui::Color32 = egui::Color32::from_rgb(0x40, 0x80, 0xE0);
+const SELECTED_COLOR: egui::Color32 = egui::Color32::from_rgb(0xF0, 0xC0, 0x20);
+
+// Splits a span on the map into the parts before and after it wraps around
+fn wrapped_spans(start: usize, len: usize) -> Vec<(usize, usize)> {
+    if start + len <= MAP_SIZE {
+        vec![(start, len)]
+    } else {
+        vec![(start, MAP_SIZE - start), (0, start + len - MAP_SIZE)]
+    }
+}
+
+fn tile_data_address(tile_number: u8, tile_data_select: bool) -> usize {
+    if tile_data_select {
+        V_RAM_ADDR as usize + tile_number as usize * TILE_BYTES
+    } else {
+        (0x9000 + (tile_number as i8) as isize * TILE_BYTES as isize) as usize
+    }
+}
+
+// CGB background map attributes: palette, tile bank, flips and priority over objects
+fn attributes_text(attributes: u8) -> String {
+    let mut parts = vec![
+        format!("palette {}", attributes & 0b111),
+        format!("bank {}", (attributes >> 3) & 1),
+    ];
+    if attributes & (1 << 5) != 0 {
+        parts.push("X flip".to_owned());
+    }
+    if attributes & (1 << 6) != 0 {
+        parts.push("Y flip".to_owned());
+    }
+    if attributes & (1 << 7) != 0 {
+        parts.push("priority".to_owned());
+    }
+
+    format!("{:02X} ({})", attributes, parts.join(", "))
+}
+
+pub struct TileMapsWindow {
+    pub open: bool,
+    map: usize,
+    scale: f32,
+    selected: Option<(usize, usize)>,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl Default for TileMapsWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            map: 0,
+            scale: 2.0,
+            selected: None,
+            texture: None,
+        }
+    }
+}
+
+impl TileMapsWindow {
+    fn map_name(map: usize, emulator: &Emulator) -> String {
+        let lcd_control = &emulator.system.graphics.registers.lcd_control;
+        let mut users = vec![];
+        if lcd_control.background_tile_map as usize == map {
+            users.push("BG");
+        }
+        if lcd_control.window_tile_map as usize == map {
+            users.push("Window");
+        }
+
+        let address = TILE_MAPS_ADDR as usize + map * TILE_MAP_BYTES;
+        match users.is_empty() {
+            true => format!("{:04X}", address),
+            false => format!("{:04X} ({})", address, users.join(", ")),
+        }
+    }
+
+    fn overlay(&self, painter: &egui::Painter, origin: egui::Pos2, emulator: &Emulator) {
+        let registers = &emulator.system.graphics.registers;
+        let lcd_control = &registers.lcd_control;
+        let rect = |x: usize, y: usize, width: usize, height: usize| {
+            egui::Rect::from_min_size(
+                origin + egui::Vec2::new(x as f32, y as f32) * self.scale,
+                egui::Vec2::new(width as f32, height as f32) * self.scale,
+            )
+        };
+
+        if lcd_control.background_tile_map as usize == self.map {
+            let x = registers.get_screen_x() as usize;
+            let y = registers.get_screen_y() as usize;
+
+            for (x, width) in wrapped_spans(x, LCD_WIDTH) {
+                for (y, height) in wrapped_spans(y, LCD_HEIGHT) {
+                    painter.rect_stroke(
+                        rect(x, y, width, height),
+                        0.0,
+                        egui::Stroke::new(2.0, VIEWPORT_COLOR),
+                        egui::StrokeKind::Inside,
+                    );
+                }
+            }
+        }
+
+        // the part of the window map which is visible starts at its top left corner
+        let window_x = registers.get_window_x() as usize;
+        let window_y = registers.get_window_y() as usize;
+        if lcd_control.window_enabled
+            && lcd_control.window_tile_map as usize == self.map
+            && window_x < LCD_WIDTH + WINDOW_X_OFFSET
+            && window_y < LCD_HEIGHT
+        {
+            let width = (LCD_WIDTH + WINDOW_X_OFFSET - window_x).min(MAP_SIZE);
+            let height = LCD_HEIGHT - window_y;
+            painter.rect_stroke(
+                rect(0, 0, width, height),
+                0.0,
+                egui::Stroke::new(2.0, WINDOW_COLOR),
+                egui::StrokeKind::Inside,
+            );
+        }
+
+        if let Some((column, row)) = self.selected {
+            painter.rect_stroke(
+                rect(column * 8, row * 8, 8, 8),
+                0.0,
+                egui::Stroke::new(1.0, SELECTED_COLOR),
+                egui::StrokeKind::Outside,
+            );
+        }
+    }
+
+    fn info_ui(&self, ui: &mut egui::Ui, emulator: &Emulator) {
+        let graphics = &emulator.system.graphics;
+        let registers = &graphics.registers;
+
+        egui::Grid::new("tile_map_info").show(ui, |ui| {
+            ui.label("SCX/SCY");
+            ui.monospace(format!(
+                "{:02X} {:02X}",
+                registers.get_screen_x(),
+                registers.get_screen_y()
+            ));
+            ui.end_row();
+
+            ui.label("WX/WY");
+            ui.monospace(format!(
+                "{:02X} {:02X}",
+                registers.get_window_x(),
+                registers.get_window_y()
+            ));
+            ui.end_row();
+
+            let Some((column, row)) = self.selected else {
+                return;
+            };
+            let tile_number = graphics.tile_maps[self.map].tiles[row][column];
+            let tile_data_select = registers.lcd_control.tile_data_select;
+
+            ui.label("Position");
+            ui.monospace(format!("{}, {}", column, row));
+            ui.end_row();
+
+            ui.label("Map address");
+            ui.monospace(format!(
+                "{:04X}",
+                TILE_MAPS_ADDR as usize + self.map * TILE_MAP_BYTES + row * TILE_MAP_WIDTH + column
+            ));
+            ui.end_row();
+
+            ui.label("Tile number");
+            ui.monospace(format!("{:02X}", tile_number));
+            ui.end_row();
+
+            ui.label("Tile address");
+            ui.monospace(format!(
+                "{:04X}",
+                tile_data_address(tile_number, tile_data_select)
+            ));
+            ui.end_row();
+
+            // attributes live in VRAM bank 1, which only exists on the CGB
+            ui.label("Attributes");
+            if emulator.system.cgb_mode() {
+                ui.monospace(attributes_text(
+                    graphics.attribute_maps[self.map].tiles[row][column],
+                ));
+            } else {
+                ui.weak("unavailable (DMG)");
+            }
+            ui.end_row();
+        });
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, emulator: &Emulator) {
+        if !self.open {
+            return;
+        }
+
+        let graphics = &emulator.system.graphics;
+        let image = graphics.tile_maps[self.map].to_image(
+            &graphics.tile_data,
+            graphics.registers.lcd_control.tile_data_select,
+            graphics.registers.get_background_palette(),
+        );
+        let texture = update_texture(ctx, &mut self.texture, "tile_map", &image);
+
+        let mut open = self.open;
+        egui::Window::new("Tile maps")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for map in 0..2 {
+                        if ui
+                            .selectable_label(self.map == map, Self::map_name(map, emulator))
+                            .clicked()
+                        {
+                            self.map = map;
+                            self.selected = None;
+                        }
+                    }
+                    ui.add(egui::Slider::new(&mut self.scale, 1.0..=3.0).step_by(1.0));
+                });
+
+                let size = egui::Vec2::splat(MAP_SIZE as f32);
+                let response = ui.add(
+                    egui::Image::new(&texture)
+                        .fit_to_exact_size(size * self.scale)
+                        .sense(egui::Sense::click()),
+                );
+
+                if response.clicked()
+                    && let Some(position) = response.interact_pointer_pos()
+                {
+                    let local = (position - response.rect.min) / self.scale;
+                    // the pointer can be exactly on the right or bottom edge
+                    let column = (local.x as usize / 8).min(TILE_MAP_WIDTH - 1);
+                    let row = (local.y as usize / 8).min(TILE_MAP_WIDTH - 1);
+                    self.selected = Some((column, row));
+                }
+
+                self.overlay(ui.painter(), response.rect.min, emulator);
+                self.info_ui(ui, emulator);
+            });
+
+        self.open = open;
+    }
+}
@@ -0,0 +1,163 @@
+use gbemu_rust_lib::prelude::{Emulator, IDENTITY_PALETTE, TileData, V_RAM_ADDR};
+
+use super::update_texture;
+
+const TILE_BYTES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PaletteSource {
+    Identity,
+    Background,
+    Object0,
+    Object1,
+}
+
+impl PaletteSource {
+    const ALL: [Self; 4] = [
+        Self::Identity,
+        Self::Background,
+        Self::Object0,
+        Self::Object1,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Identity => "Color numbers",
+            Self::Background => "BGP",
+            Self::Object0 => "OBP0",
+            Self::Object1 => "OBP1",
+        }
+    }
+
+    fn value(&self, emulator: &Emulator) -> u8 {
+        let registers = &emulator.system.graphics.registers;
+        match self {
+            Self::Identity => IDENTITY_PALETTE,
+            Self::Background => registers.get_background_palette(),
+            Self::Object0 => registers.get_obj_palette(0),
+            Self::Object1 => registers.get_obj_palette(1),
+        }
+    }
+}
+
+pub struct TilesWindow {
+    pub open: bool,
+    palette: PaletteSource,
+    bank: usize,
+    scale: f32,
+    texture: Option<egui::TextureHandle>,
+}
+
+impl Default for TilesWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            palette: PaletteSource::Identity,
+            bank: 0,
+            scale: 2.0,
+            texture: None,
+        }
+    }
+}
+
+fn tooltip_ui(ui: &mut egui::Ui, emulator: &Emulator, index: usize, preview: egui::Image) {
+    let active_mode = emulator
+        .system
+        .graphics
+        .registers
+        .lcd_control
+        .tile_data_select;
+
+    ui.horizontal(|ui| {
+        ui.add(preview.fit_to_exact_size(egui::Vec2::splat(64.0)));
+
+        egui::Grid::new("tile_tooltip").show(ui, |ui| {
+            ui.label("Tile");
+            ui.monospace(format!("{}", index));
+            ui.end_row();
+
+            ui.label("Address");
+            ui.monospace(format!("{:04X}", V_RAM_ADDR as usize + index * TILE_BYTES));
+            ui.end_row();
+
+            for (tile_data_select, mode) in [(true, "8000"), (false, "8800")] {
+                ui.label(format!("{} mode", mode));
+                let number = match TileData::tile_number(index, tile_data_select) {
+                    Some(number) => format!("{:02X}", number),
+                    None => "-".to_owned(),
+                };
+                if tile_data_select == active_mode {
+                    ui.label(egui::RichText::new(format!("{} (LCDC.4)", number)).monospace());
+                } else {
+                    ui.monospace(number);
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+
+impl TilesWindow {
+    pub fn show(&mut self, ctx: &egui::Context, emulator: &Emulator) {
+        if !self.open {
+            return;
+        }
+
+        // VRAM bank 1 only exists on the CGB
+        let cgb_mode = emulator.system.cgb_mode();
+        if !cgb_mode {
+            self.bank = 0;
+        }
+
+        let image = emulator
+            .system
+            .graphics
+            .tile_data(self.bank)
+            .to_image(self.palette.value(emulator));
+        let texture = update_texture(ctx, &mut self.texture, "tile_data", &image);
+
+        egui::Window::new("Tile data")
+            .open(&mut self.open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_label("Palette")
+                        .selected_text(self.palette.name())
+                        .show_ui(ui, |ui| {
+                            for palette in PaletteSource::ALL {
+                                ui.selectable_value(&mut self.palette, palette, palette.name());
+                            }
+                        });
+                    if cgb_mode {
+                        for bank in 0..2 {
+                            ui.selectable_value(&mut self.bank, bank, format!("Bank {}", bank));
+                        }
+                    }
+                    ui.add(egui::Slider::new(&mut self.scale, 1.0..=4.0).step_by(1.0));
+                });
+
+                let size = egui::Vec2::new(image.width as f32, image.height as f32);
+                let response = ui.add(
+                    egui::Image::new(&texture)
+                        .fit_to_exact_size(size * self.scale)
+                        .sense(egui::Sense::hover()),
+                );
+
+                if let Some(position) = response.hover_pos() {
+                    let local = (position - response.rect.min) / self.scale;
+                    // the pointer can be exactly on the right or bottom edge
+                    let column = (local.x as usize / 8).min(image.width / 8 - 1);
+                    let row = (local.y as usize / 8).min(image.height / 8 - 1);
+                    let index = row * (image.width / 8) + column;
+
+                    let uv = egui::Rect::from_min_size(
+                        (egui::Vec2::new(column as f32, row as f32) * 8.0 / size).to_pos2(),
+                        egui::Vec2::splat(8.0) / size,
+                    );
+                    let preview = egui::Image::new(&texture).uv(uv);
+
+                    response.on_hover_ui_at_pointer(|ui| tooltip_ui(ui, emulator, index, preview));
+                }
+            });
+    }
+}
@@ -41,6 +41,10 @@ impl InputHandler {
             return Some(Action::TogglePause);
         }
 
+        if *modifiers == egui::Modifiers::NONE && *key == egui::Key::F12 && *pressed {
+            return Some(Action::SaveScreenshot);
+        }
+
         // emulator inputs are not allowed to have modifiers
         if *modifiers != egui::Modifiers::NONE {
             return None;
@@ -1,15 +1,13 @@
 use poll_promise::Promise;
 
 #[cfg(not(target_arch = "wasm32"))]
-pub fn execute<F: std::future::Future<Output = Option<Vec<u8>>> + Send + 'static>(
+pub fn execute<T: Send + 'static, F: std::future::Future<Output = T> + Send + 'static>(
     f: F,
-) -> Promise<Option<Vec<u8>>> {
+) -> Promise<T> {
     Promise::spawn_async(f)
 }
 
 #[cfg(target_arch = "wasm32")]
-pub fn execute<F: std::future::Future<Output = Option<Vec<u8>>> + 'static>(
-    f: F,
-) -> Promise<Option<Vec<u8>>> {
+pub fn execute<T: 'static, F: std::future::Future<Output = T> + 'static>(f: F) -> Promise<T> {
     Promise::spawn_local(f)
 }
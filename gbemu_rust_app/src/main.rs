@@ -5,12 +5,19 @@ mod app;
 
 use app::GbemuApp;
 use clap::Parser;
+use gbemu_rust_lib::prelude::Model;
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
 struct Args {
     #[arg(short, long)]
     file_path: Option<String>,
+
+    #[arg(short, long)]
+    boot_rom_path: Option<String>,
+
+    #[arg(short, long, default_value_t = Model::default())]
+    model: Model,
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -35,15 +42,41 @@ async fn main() {
 
     let args = Args::parse();
     let mut emulator: Option<Emulator> = None;
+    let mut boot_rom: Option<Vec<u8>> = None;
+    let mut load_error: Option<String> = None;
+
+    if let Some(boot_rom_path) = args.boot_rom_path.as_deref() {
+        match std::fs::read(boot_rom_path) {
+            Ok(data) => boot_rom = Some(data),
+            Err(err) => {
+                log::error!("{}", err);
+                load_error = Some(format!("Could not read {}: {}", boot_rom_path, err));
+            },
+        }
+    }
 
     if let Some(file_path) = args.file_path.as_deref() {
         match std::fs::read(file_path) {
             Ok(rom) => {
-                emulator = Some(Emulator::new_from_buffer(rom, true, None, None).unwrap());
+                match Emulator::new_from_buffer_with_model(
+                    rom,
+                    args.model,
+                    boot_rom.clone(),
+                    true,
+                    None,
+                    None,
+                ) {
+                    Ok(e) => emulator = Some(e),
+                    Err(err) => {
+                        log::error!("{}", err);
+                        load_error.get_or_insert(format!("Could not load {}: {}", file_path, err));
+                    },
+                }
             },
             Err(err) => {
                 log::error!("{}", err);
-                todo!("Handle error if file read is not successful!");
+                // the boot ROM error is reported first
+                load_error.get_or_insert(format!("Could not read {}: {}", file_path, err));
             },
         }
     }
@@ -51,7 +84,14 @@ async fn main() {
     eframe::run_native(
         "gbemu",
         native_options,
-        Box::new(|cc| Ok(Box::new(GbemuApp::new(&cc, emulator).unwrap()))),
+        Box::new(|cc| {
+            let mut app = GbemuApp::new(&cc, emulator, args.model, boot_rom).unwrap();
+            if let Some(error) = load_error {
+                app.show_error(error);
+            }
+
+            Ok(Box::new(app))
+        }),
     )
     .unwrap();
 }
@@ -95,7 +135,11 @@ fn main() {
             .start(
                 canvas,
                 web_options,
-                Box::new(|cc| Ok(Box::new(GbemuApp::new(&cc, None).unwrap()))),
+                Box::new(|cc| {
+                    Ok(Box::new(
+                        GbemuApp::new(&cc, None, Model::default(), None).unwrap(),
+                    ))
+                }),
             )
             .await;
 
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use gbemu_rust_lib::prelude::{Emulator, GdbServer, Model, TraceFormat};
+
+const EXIT_ERROR: u8 = 2;
+
+#[derive(clap::Args)]
+pub struct GdbArgs {
+    #[arg(help = "ROM file to debug")]
+    rom: PathBuf,
+
+    #[arg(short, long, default_value_t = Model::default())]
+    model: Model,
+
+    #[arg(short, long)]
+    boot_rom_path: Option<PathBuf>,
+
+    #[arg(
+        short,
+        long,
+        default_value_t = 2345,
+        help = "Local TCP port to listen on"
+    )]
+    port: u16,
+
+    #[arg(long, help = "Keep listening for new clients after one disconnected")]
+    persistent: bool,
+
+    #[arg(
+        long,
+        help = "Write the last executed instructions to this file whenever execution stops"
+    )]
+    trace: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value_t = 10_000,
+        help = "Number of instructions kept for --trace"
+    )]
+    trace_length: usize,
+
+    #[arg(long, default_value_t = TraceFormat::GameboyDoctor, help = "Format of --trace: doctor, bgb or json")]
+    trace_format: TraceFormat,
+}
+
+fn serve(args: GdbArgs) -> Result<(), String> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))
+    };
+
+    let rom = read(&args.rom)?;
+    let boot_rom = args.boot_rom_path.as_deref().map(read).transpose()?;
+    let mut emulator =
+        Emulator::new_from_buffer_with_model(rom, args.model, boot_rom, true, None, None)
+            .map_err(|err| format!("Could not load {}: {}", args.rom.display(), err))?;
+
+    if let Some(path) = args.trace {
+        emulator.trace.enable(args.trace_length);
+        emulator
+            .trace
+            .set_dump_on_stop(Some((path, args.trace_format)));
+    }
+
+    let server = GdbServer::bind(("127.0.0.1", args.port))
+        .map_err(|err| format!("Could not listen on port {}: {}", args.port, err))?;
+    eprintln!(
+        "Waiting for GDB on port {}, connect with 'target remote localhost:{}'",
+        args.port, args.port
+    );
+
+    loop {
+        server
+            .serve(&mut emulator)
+            .map_err(|err| format!("GDB connection failed: {}", err))?;
+
+        if !args.persistent {
+            return Ok(());
+        }
+    }
+}
+
+pub fn run(args: GdbArgs) -> ExitCode {
+    match serve(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(EXIT_ERROR)
+        },
+    }
+}
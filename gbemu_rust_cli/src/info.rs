@@ -0,0 +1,230 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use gbemu_rust_lib::prelude::{CartridgeHeader, Emulator};
+use serde_json::{Value, json};
+
+const ROM_BANK_KIB: usize = 16;
+const RAM_BANK_KIB: usize = 8;
+
+#[derive(clap::Args)]
+pub struct InfoArgs {
+    #[arg(required = true, help = "ROM files to inspect")]
+    files: Vec<PathBuf>,
+
+    #[arg(short, long, help = "Print the result as JSON")]
+    json: bool,
+}
+
+struct RomInfo {
+    path: PathBuf,
+    result: Result<(CartridgeHeader, Option<String>), String>,
+}
+
+impl RomInfo {
+    fn read(path: PathBuf) -> Self {
+        let result = std::fs::read(&path)
+            .map_err(|err| err.to_string())
+            .and_then(|rom| {
+                let header =
+                    CartridgeHeader::try_from(rom.as_slice()).map_err(|err| err.to_string())?;
+
+                // a valid header does not mean that the emulator supports the cartridge
+                let load_error = Emulator::new_from_buffer(rom, false, None, None)
+                    .err()
+                    .map(|err| err.to_string());
+
+                Ok((header, load_error))
+            });
+
+        Self { path, result }
+    }
+
+    fn to_json(&self) -> Value {
+        let path = self.path.display().to_string();
+
+        match &self.result {
+            Ok((header, load_error)) => json!({
+                "path": path,
+                "title": header.title,
+                "manufacturer_code": header.manufacturer_code,
+                "cartridge_type": header.cartridge_type,
+                "mapper": header.cartridge_type_name(),
+                "rom_banks": header.rom_banks,
+                "rom_size": header.rom_banks * ROM_BANK_KIB * 1024,
+                "ram_banks": header.ram_banks,
+                "ram_size": header.ram_banks * RAM_BANK_KIB * 1024,
+                "cgb_support": header.cgb_support.to_string(),
+                "sgb_support": header.sgb_support,
+                "licensee_code": header.licensee_code(),
+                "destination": header.destination.to_string(),
+                "mask_rom_version": header.mask_rom_version,
+                "entry_point": header.entry_point,
+                "logo_valid": header.logo_valid(),
+                "header_checksum": {
+                    "expected": header.header_checksum.expected,
+                    "computed": header.header_checksum.computed,
+                    "valid": header.header_checksum.is_valid(),
+                },
+                "global_checksum": {
+                    "expected": header.global_checksum.expected,
+                    "computed": header.global_checksum.computed,
+                    "valid": header.global_checksum.is_valid(),
+                },
+                "supported": load_error.is_none(),
+                "error": load_error,
+            }),
+            Err(err) => json!({
+                "path": path,
+                "supported": false,
+                "error": err,
+            }),
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = vec![self.path.display().to_string()];
+
+        let (header, load_error) = match &self.result {
+            Ok(result) => result,
+            Err(err) => {
+                lines.push(format!("  Error:            {}", err));
+                return lines.join("\n");
+            },
+        };
+
+        let valid = |valid: bool| if valid { "valid" } else { "invalid" };
+        let checksum = |valid: bool, computed: String| {
+            if valid {
+                "valid".to_owned()
+            } else {
+                format!("invalid, computed {}", computed)
+            }
+        };
+
+        lines.extend([
+            format!("  Title:            {}", header.title),
+            format!(
+                "  Manufacturer:     {}",
+                header.manufacturer_code.as_deref().unwrap_or("-")
+            ),
+            format!(
+                "  Cartridge type:   0x{:02X} ({})",
+                header.cartridge_type,
+                header.cartridge_type_name()
+            ),
+            format!(
+                "  ROM size:         {} KiB ({} banks)",
+                header.rom_banks * ROM_BANK_KIB,
+                header.rom_banks
+            ),
+            format!(
+                "  RAM size:         {} KiB ({} banks)",
+                header.ram_banks * RAM_BANK_KIB,
+                header.ram_banks
+            ),
+            format!("  CGB support:      {}", header.cgb_support),
+            format!(
+                "  SGB support:      {}",
+                if header.sgb_support { "yes" } else { "no" }
+            ),
+            format!("  Licensee:         {}", header.licensee_code()),
+            format!("  Destination:      {}", header.destination),
+            format!("  Mask ROM version: {}", header.mask_rom_version),
+            format!("  Logo:             {}", valid(header.logo_valid())),
+            format!(
+                "  Header checksum:  0x{:02X} ({})",
+                header.header_checksum.expected,
+                checksum(
+                    header.header_checksum.is_valid(),
+                    format!("0x{:02X}", header.header_checksum.computed)
+                )
+            ),
+            format!(
+                "  Global checksum:  0x{:04X} ({})",
+                header.global_checksum.expected,
+                checksum(
+                    header.global_checksum.is_valid(),
+                    format!("0x{:04X}", header.global_checksum.computed)
+                )
+            ),
+            match load_error {
+                None => "  Supported:        yes".to_owned(),
+                Some(err) => format!("  Supported:        no ({})", err),
+            },
+        ]);
+
+        lines.join("\n")
+    }
+}
+
+pub fn run(args: InfoArgs) -> ExitCode {
+    let infos: Vec<RomInfo> = args.files.into_iter().map(RomInfo::read).collect();
+
+    if args.json {
+        let values: Vec<Value> = infos.iter().map(RomInfo::to_json).collect();
+        println!("{}", serde_json::to_string_pretty(&values).unwrap());
+    } else {
+        let texts: Vec<String> = infos.iter().map(RomInfo::to_text).collect();
+        println!("{}", texts.join("\n\n"));
+    }
+
+    if infos.iter().all(|info| info.result.is_ok()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom_info(rom: Vec<u8>) -> RomInfo {
+        let path = std::env::temp_dir().join(format!("gbemu_info_{}.gb", rom[0x0147]));
+        std::fs::write(&path, rom).unwrap();
+
+        let info = RomInfo::read(path.clone());
+        std::fs::remove_file(path).unwrap();
+
+        info
+    }
+
+    #[test]
+    fn test_supported_rom() {
+        let mut rom = vec![0; 32 * 1024];
+        rom[0x0134..0x0138].copy_from_slice(b"TEST");
+
+        let info = rom_info(rom);
+        let value = info.to_json();
+
+        assert_eq!(value["title"], "TEST");
+        assert_eq!(value["mapper"], "ROM ONLY");
+        assert_eq!(value["rom_size"], 32 * 1024);
+        assert_eq!(value["supported"], true);
+        assert_eq!(value["header_checksum"]["valid"], false);
+        assert!(info.to_text().contains("  Supported:        yes"));
+    }
+
+    #[test]
+    fn test_unsupported_rom() {
+        let mut rom = vec![0; 32 * 1024];
+        rom[0x0147] = 0x13;
+
+        let info = rom_info(rom);
+        let value = info.to_json();
+
+        assert_eq!(value["mapper"], "MBC3+RAM+BATTERY");
+        assert_eq!(value["supported"], false);
+        assert_eq!(value["error"], "Unsupported cartridge type 0x13");
+    }
+
+    #[test]
+    fn test_missing_file() {
+        let info = RomInfo::read(PathBuf::from("/nonexistent/rom.gb"));
+
+        assert!(info.result.is_err());
+        assert_eq!(info.to_json()["supported"], false);
+        assert!(info.to_text().contains("Error:"));
+    }
+}
@@ -0,0 +1,34 @@
+mod gdb;
+mod info;
+mod run;
+
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser)]
+#[command(version, about = "Command line tools for gbemu", long_about = None)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    #[command(about = "Debug a ROM with GDB over the remote serial protocol")]
+    Gdb(gdb::GdbArgs),
+    #[command(about = "Print the cartridge header of one or more ROM files")]
+    Info(info::InfoArgs),
+    #[command(about = "Run a ROM without a display")]
+    Run(run::RunArgs),
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+
+    match args.command {
+        Command::Gdb(args) => gdb::run(args),
+        Command::Info(args) => info::run(args),
+        Command::Run(args) => run::run(args),
+    }
+}
@@ -0,0 +1,413 @@
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use gbemu_rust_lib::prelude::{
+    CaptureSerial, Emulator, FRAME_CYCLES, GRAYSCALE_PALETTE, Key, Model, SerialCapture,
+    TraceFormat,
+};
+
+const EXIT_FAILURE: u8 = 1;
+const EXIT_ERROR: u8 = 2;
+// --until without --frames or --cycles gives up after a minute of emulated time
+const UNTIL_MAX_FRAMES: u64 = 3600;
+
+#[derive(clap::Args)]
+#[command(after_help = "Exit codes:
+  0  the run completed or the --until pattern was found
+  1  the --fail-on pattern was found or the --until pattern was not found
+  2  the ROM could not be loaded or executed")]
+pub struct RunArgs {
+    #[arg(help = "ROM file to run")]
+    rom: PathBuf,
+
+    #[arg(short, long, default_value_t = Model::default())]
+    model: Model,
+
+    #[arg(short, long)]
+    boot_rom_path: Option<PathBuf>,
+
+    #[arg(long, help = "Run for this many frames")]
+    frames: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Run for this many emulator steps",
+        conflicts_with = "frames"
+    )]
+    cycles: Option<u64>,
+
+    #[arg(
+        long,
+        help = "Stop successfully once this text was sent over serial, at most 3600 frames \
+                without --frames or --cycles"
+    )]
+    until: Option<String>,
+
+    #[arg(long, help = "Stop with a failure once this text was sent over serial")]
+    fail_on: Option<String>,
+
+    #[arg(
+        short,
+        long,
+        help = "Input script with lines of the form '<frame> <press|release> <key>'"
+    )]
+    input: Option<PathBuf>,
+
+    #[arg(short, long, help = "Write the final framebuffer to this PNG file")]
+    screenshot: Option<PathBuf>,
+
+    #[arg(long, default_value_t = 1, help = "Integer scale of the screenshot")]
+    scale: usize,
+
+    #[arg(
+        long,
+        help = "Write the serial transcript to this file ('-' for stdout)"
+    )]
+    serial: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Write the last executed instructions to this file, also after an execution error"
+    )]
+    trace: Option<PathBuf>,
+
+    #[arg(
+        long,
+        default_value_t = 10_000,
+        help = "Number of instructions kept for --trace"
+    )]
+    trace_length: usize,
+
+    #[arg(long, default_value_t = TraceFormat::GameboyDoctor, help = "Format of --trace: doctor, bgb or json")]
+    trace_format: TraceFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct InputEvent {
+    frame: u64,
+    key: Key,
+    pressed: bool,
+}
+
+fn parse_input_script(script: &str) -> Result<Vec<InputEvent>, String> {
+    let mut events = vec![];
+
+    for (index, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let error = |message: String| format!("Input script line {}: {}", index + 1, message);
+        let [frame, action, key] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+            return Err(error(format!(
+                "Expected '<frame> <action> <key>', got '{}'",
+                line
+            )));
+        };
+
+        events.push(InputEvent {
+            frame: frame
+                .parse()
+                .map_err(|_| error(format!("Invalid frame '{}'", frame)))?,
+            pressed: match action {
+                "press" => true,
+                "release" => false,
+                _ => return Err(error(format!("Unknown action '{}'", action))),
+            },
+            key: key.parse().map_err(error)?,
+        });
+    }
+
+    events.sort_by_key(|event| event.frame);
+
+    Ok(events)
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum RunResult {
+    Completed,
+    PatternFound,
+    FailurePatternFound,
+    PatternNotFound,
+}
+
+struct Runner {
+    emulator: Emulator,
+    capture: SerialCapture,
+    events: Vec<InputEvent>,
+}
+
+impl Runner {
+    fn run(
+        &mut self,
+        max_cycles: u64,
+        until: Option<&str>,
+        fail_on: Option<&str>,
+    ) -> Result<RunResult, String> {
+        let mut events = self.events.iter().peekable();
+        let mut serial_len = 0;
+
+        for cycle in 0..max_cycles {
+            while let Some(event) = events.next_if(|e| e.frame * FRAME_CYCLES as u64 <= cycle) {
+                self.emulator
+                    .system
+                    .io
+                    .joypad
+                    .key_event(event.key, event.pressed);
+            }
+
+            self.emulator
+                .step()
+                .map_err(|err| format!("Execution error at cycle {}: {:?}", cycle, err))?;
+
+            // only search the transcript if something new was sent
+            if self.capture.len() != serial_len {
+                serial_len = self.capture.len();
+
+                if fail_on.is_some_and(|pattern| self.capture.contains(pattern)) {
+                    return Ok(RunResult::FailurePatternFound);
+                }
+                if until.is_some_and(|pattern| self.capture.contains(pattern)) {
+                    return Ok(RunResult::PatternFound);
+                }
+            }
+        }
+
+        Ok(if until.is_some() {
+            RunResult::PatternNotFound
+        } else {
+            RunResult::Completed
+        })
+    }
+
+    fn write_screenshot(&self, path: &Path, scale: usize) -> Result<(), String> {
+        self.emulator
+            .screenshot()
+            .write_png(path, &GRAYSCALE_PALETTE, scale)
+            .map_err(|err| format!("Could not write {}: {}", path.display(), err))
+    }
+
+    fn write_trace(&self, path: &Path, format: TraceFormat) -> Result<(), String> {
+        self.emulator
+            .trace
+            .save(path, format, &self.emulator.debugger.symbols)
+            .map_err(|err| format!("Could not write {}: {}", path.display(), err))
+    }
+
+    fn write_serial(&self, path: &Path) -> Result<(), String> {
+        if path == Path::new("-") {
+            print!("{}", self.capture.transcript());
+            Ok(())
+        } else {
+            std::fs::write(path, self.capture.data()).map_err(|err| err.to_string())
+        }
+    }
+}
+
+fn max_cycles(frames: Option<u64>, cycles: Option<u64>, until: bool) -> Result<u64, String> {
+    match (frames, cycles, until) {
+        (Some(frames), _, _) => Ok(frames * FRAME_CYCLES as u64),
+        (_, Some(cycles), _) => Ok(cycles),
+        (None, None, true) => Ok(UNTIL_MAX_FRAMES * FRAME_CYCLES as u64),
+        (None, None, false) => Err("One of --frames, --cycles or --until is required".to_owned()),
+    }
+}
+
+fn run_rom(args: RunArgs) -> Result<RunResult, String> {
+    let read = |path: &Path| {
+        std::fs::read(path).map_err(|err| format!("Could not read {}: {}", path.display(), err))
+    };
+
+    let rom = read(&args.rom)?;
+    let boot_rom = args.boot_rom_path.as_deref().map(read).transpose()?;
+    let events = match args.input.as_deref() {
+        Some(path) => parse_input_script(
+            &std::fs::read_to_string(path)
+                .map_err(|err| format!("Could not read {}: {}", path.display(), err))?,
+        )?,
+        None => vec![],
+    };
+
+    let max_cycles = max_cycles(args.frames, args.cycles, args.until.is_some())?;
+
+    let serial = CaptureSerial::new();
+    let capture = serial.capture();
+    let mut emulator = Emulator::new_from_buffer_with_model(
+        rom,
+        args.model,
+        boot_rom,
+        true,
+        None,
+        Some(Box::new(serial)),
+    )
+    .map_err(|err| format!("Could not load {}: {}", args.rom.display(), err))?;
+
+    if args.trace.is_some() {
+        emulator.trace.enable(args.trace_length);
+    }
+
+    let mut runner = Runner {
+        emulator,
+        capture,
+        events,
+    };
+    let result = runner.run(max_cycles, args.until.as_deref(), args.fail_on.as_deref());
+
+    // write the outputs even if the emulator stopped with an error
+    if let Some(path) = args.screenshot.as_deref() {
+        runner.write_screenshot(path, args.scale)?;
+    }
+    if let Some(path) = args.serial.as_deref() {
+        runner.write_serial(path)?;
+    }
+    if let Some(path) = args.trace.as_deref() {
+        runner.write_trace(path, args.trace_format)?;
+    }
+
+    result
+}
+
+pub fn run(args: RunArgs) -> ExitCode {
+    match run_rom(args) {
+        Ok(RunResult::Completed | RunResult::PatternFound) => ExitCode::SUCCESS,
+        Ok(result) => {
+            eprintln!("{:?}", result);
+            ExitCode::from(EXIT_FAILURE)
+        },
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::from(EXIT_ERROR)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gbemu_rust_lib::prelude::LCD_HEIGHT;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_input_script() {
+        let events = parse_input_script(
+            "# title screen\n\
+             65 release start\n\
+             60 press START # hold for 5 frames\n\
+             \n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                InputEvent {
+                    frame: 60,
+                    key: Key::Start,
+                    pressed: true
+                },
+                InputEvent {
+                    frame: 65,
+                    key: Key::Start,
+                    pressed: false
+                },
+            ]
+        );
+
+        assert!(parse_input_script("60 press").is_err());
+        assert!(parse_input_script("60 hold a").is_err());
+        assert!(parse_input_script("x press a").is_err());
+        assert!(parse_input_script("60 press z").is_err());
+    }
+
+    fn print_rom(text: &[u8]) -> Vec<u8> {
+        let mut rom_buffer = vec![0; 32 * 1024];
+        let code = [
+            0x21, 0x00, 0x02, // LD HL, 0x0200
+            0x2A, // LD A, (HL+)
+            0xB7, // OR A
+            0x28, 0x08, // JR Z, +8
+            0xE0, 0x01, // LDH (SB), A
+            0x3E, 0x81, // LD A, 0x81
+            0xE0, 0x02, // LDH (SC), A
+            0x18, 0xF4, // JR -12
+            0x18, 0xFE, // JR -2
+        ];
+        rom_buffer[0x0100..0x0100 + code.len()].copy_from_slice(&code);
+        rom_buffer[0x0200..0x0200 + text.len()].copy_from_slice(text);
+
+        rom_buffer
+    }
+
+    fn new_runner(text: &[u8]) -> Runner {
+        let serial = CaptureSerial::new();
+        let capture = serial.capture();
+
+        Runner {
+            emulator: Emulator::new_from_buffer(
+                print_rom(text),
+                true,
+                None,
+                Some(Box::new(serial)),
+            )
+            .unwrap(),
+            capture,
+            events: vec![],
+        }
+    }
+
+    #[test]
+    fn test_run_until() {
+        let mut runner = new_runner(b"Passed\n");
+
+        assert_eq!(
+            runner.run(10_000, Some("Passed"), Some("Failed")),
+            Ok(RunResult::PatternFound)
+        );
+        assert_eq!(runner.capture.transcript(), "Passed");
+
+        let mut runner = new_runner(b"Failed\n");
+        assert_eq!(
+            runner.run(10_000, Some("Passed"), Some("Failed")),
+            Ok(RunResult::FailurePatternFound)
+        );
+
+        let mut runner = new_runner(b"Nothing\n");
+        assert_eq!(
+            runner.run(10_000, Some("Passed"), None),
+            Ok(RunResult::PatternNotFound)
+        );
+        assert_eq!(runner.run(100, None, None), Ok(RunResult::Completed));
+    }
+
+    #[test]
+    fn test_max_cycles() {
+        assert_eq!(max_cycles(Some(2), None, true), Ok(2 * FRAME_CYCLES as u64));
+        assert_eq!(max_cycles(None, Some(100), true), Ok(100));
+        assert_eq!(
+            max_cycles(None, None, true),
+            Ok(UNTIL_MAX_FRAMES * FRAME_CYCLES as u64)
+        );
+        assert!(max_cycles(None, None, false).is_err());
+    }
+
+    #[test]
+    fn test_run_one_frame() {
+        let mut runner = new_runner(b"");
+        assert_eq!(runner.emulator.system.graphics.registers.get_lcd_ly(), 0);
+
+        // --frames 1 has to pass through exactly one VBlank and end at the top of the next frame
+        let mut v_blanks = 0;
+        for _ in 0..FRAME_CYCLES {
+            let ly = runner.emulator.system.graphics.registers.get_lcd_ly();
+            assert_eq!(runner.run(1, None, None), Ok(RunResult::Completed));
+            let next_ly = runner.emulator.system.graphics.registers.get_lcd_ly();
+            if ly != next_ly && next_ly == LCD_HEIGHT as u8 {
+                v_blanks += 1;
+            }
+        }
+
+        assert_eq!(v_blanks, 1);
+        assert_eq!(runner.emulator.system.graphics.registers.get_lcd_ly(), 0);
+    }
+}
@@ -1,40 +1,242 @@
-use tracing::warn;
+use std::fmt::Display;
 
+use crate::emulator::LoadError;
 use crate::memory::mbc::MbcType;
 
+const ENTRY_POINT_ADDR: usize = 0x0100;
+const LOGO_ADDR: usize = 0x0104;
+const TITLE_ADDR: usize = 0x0134;
+const MANUFACTURER_CODE_ADDR: usize = 0x013F;
+const CGB_FLAG_ADDR: usize = 0x0143;
+const NEW_LICENSEE_CODE_ADDR: usize = 0x0144;
+const SGB_FLAG_ADDR: usize = 0x0146;
+const CARTRIDGE_TYPE_ADDR: usize = 0x0147;
+const ROM_SIZE_ADDR: usize = 0x0148;
+const RAM_SIZE_ADDR: usize = 0x0149;
+const DESTINATION_CODE_ADDR: usize = 0x014A;
+const OLD_LICENSEE_CODE_ADDR: usize = 0x014B;
+const MASK_ROM_VERSION_ADDR: usize = 0x014C;
+const HEADER_CHECKSUM_ADDR: usize = 0x014D;
+const GLOBAL_CHECKSUM_ADDR: usize = 0x014E;
+const HEADER_END: usize = 0x0150;
+
+const LOGO_SIZE: usize = 48;
+const NINTENDO_LOGO: [u8; LOGO_SIZE] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+// the old licensee code 0x33 means that the new licensee code is used instead
+const USE_NEW_LICENSEE_CODE: u8 = 0x33;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CgbSupport {
+    None,
+    Compatible,
+    Only,
+}
+
+impl From<u8> for CgbSupport {
+    fn from(value: u8) -> Self {
+        match value {
+            0xC0 => CgbSupport::Only,
+            // the CGB only checks bit 7
+            _ if value & 0x80 > 0 => CgbSupport::Compatible,
+            _ => CgbSupport::None,
+        }
+    }
+}
+
+impl Display for CgbSupport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CgbSupport::None => "None",
+            CgbSupport::Compatible => "Compatible",
+            CgbSupport::Only => "CGB only",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Destination {
+    Japan,
+    Overseas,
+    Unknown(u8),
+}
+
+impl From<u8> for Destination {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => Destination::Japan,
+            0x01 => Destination::Overseas,
+            _ => Destination::Unknown(value),
+        }
+    }
+}
+
+impl Display for Destination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Destination::Japan => f.write_str("Japan"),
+            Destination::Overseas => f.write_str("Overseas"),
+            Destination::Unknown(value) => write!(f, "Unknown (0x{:02X})", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checksum<T> {
+    pub expected: T,
+    pub computed: T,
+}
+
+impl<T: PartialEq> Checksum<T> {
+    pub fn is_valid(&self) -> bool {
+        self.expected == self.computed
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CartridgeHeader {
+    pub entry_point: [u8; 4],
+    pub logo: [u8; LOGO_SIZE],
     pub title: String,
-    pub mbc_type: MbcType,
+    pub manufacturer_code: Option<String>,
+    pub cgb_support: CgbSupport,
+    pub new_licensee_code: String,
+    pub sgb_support: bool,
+    pub cartridge_type: u8,
     pub rom_banks: usize,
     pub ram_banks: usize,
+    pub destination: Destination,
+    pub old_licensee_code: u8,
+    pub mask_rom_version: u8,
+    pub header_checksum: Checksum<u8>,
+    pub global_checksum: Checksum<u16>,
 }
 
-const MBC_TYPE_ADDR: usize = 0x148;
-const ROM_SIZE_ADDR: usize = 0x148;
-const RAM_SIZE_ADDR: usize = 0x148;
-
-impl From<&Vec<u8>> for CartridgeHeader {
-    fn from(buffer: &Vec<u8>) -> Self {
-        if buffer.len() < 0x014F {
-            warn!("Could not parse cartridge header. Using default MBC Mbc0");
-
-            return CartridgeHeader {
-                title: "Unknown".to_owned(),
-                mbc_type: MbcType::Mbc0,
-                rom_banks: 32 * 1024,
-                ram_banks: 0,
-            };
+impl CartridgeHeader {
+    pub fn mbc_type(&self) -> Result<MbcType, LoadError> {
+        self.cartridge_type.try_into()
+    }
+
+    pub fn cartridge_type_name(&self) -> &'static str {
+        match self.cartridge_type {
+            0x00 => "ROM ONLY",
+            0x01 => "MBC1",
+            0x02 => "MBC1+RAM",
+            0x03 => "MBC1+RAM+BATTERY",
+            0x05 => "MBC2",
+            0x06 => "MBC2+BATTERY",
+            0x08 => "ROM+RAM",
+            0x09 => "ROM+RAM+BATTERY",
+            0x0B => "MMM01",
+            0x0C => "MMM01+RAM",
+            0x0D => "MMM01+RAM+BATTERY",
+            0x0F => "MBC3+TIMER+BATTERY",
+            0x10 => "MBC3+TIMER+RAM+BATTERY",
+            0x11 => "MBC3",
+            0x12 => "MBC3+RAM",
+            0x13 => "MBC3+RAM+BATTERY",
+            0x19 => "MBC5",
+            0x1A => "MBC5+RAM",
+            0x1B => "MBC5+RAM+BATTERY",
+            0x1C => "MBC5+RUMBLE",
+            0x1D => "MBC5+RUMBLE+RAM",
+            0x1E => "MBC5+RUMBLE+RAM+BATTERY",
+            0x20 => "MBC6",
+            0x22 => "MBC7+SENSOR+RUMBLE+RAM+BATTERY",
+            0xFC => "POCKET CAMERA",
+            0xFD => "BANDAI TAMA5",
+            0xFE => "HuC3",
+            0xFF => "HuC1+RAM+BATTERY",
+            _ => "UNKNOWN",
         }
+    }
+
+    pub fn licensee_code(&self) -> String {
+        if self.old_licensee_code == USE_NEW_LICENSEE_CODE {
+            self.new_licensee_code.clone()
+        } else {
+            format!("{:02X}", self.old_licensee_code)
+        }
+    }
 
-        let mut title = String::new();
-        for byte in &buffer[0x0134..0x0143] {
-            if *byte == 0x00 {
-                break;
+    pub fn logo_valid(&self) -> bool {
+        self.logo == NINTENDO_LOGO
+    }
+
+    // the boot ROM refuses to start a cartridge with an invalid logo or header checksum
+    pub fn is_valid(&self) -> bool {
+        self.logo_valid() && self.header_checksum.is_valid()
+    }
+}
+
+pub fn header_checksum(buffer: &[u8]) -> u8 {
+    buffer[TITLE_ADDR..HEADER_CHECKSUM_ADDR]
+        .iter()
+        .fold(0u8, |checksum, byte| {
+            checksum.wrapping_sub(*byte).wrapping_sub(1)
+        })
+}
+
+pub fn global_checksum(buffer: &[u8]) -> u16 {
+    buffer
+        .iter()
+        .enumerate()
+        .filter(|(address, _)| !(GLOBAL_CHECKSUM_ADDR..HEADER_END).contains(address))
+        .fold(0u16, |checksum, (_, byte)| {
+            checksum.wrapping_add(*byte as u16)
+        })
+}
+
+fn read_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|byte| **byte != 0x00)
+        .map(|byte| {
+            if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                char::REPLACEMENT_CHARACTER
             }
+        })
+        .collect()
+}
 
-            title.push(*byte as char);
+impl TryFrom<&[u8]> for CartridgeHeader {
+    type Error = LoadError;
+
+    fn try_from(buffer: &[u8]) -> Result<Self, Self::Error> {
+        if buffer.len() < HEADER_END {
+            return Err(LoadError::TruncatedRom {
+                expected: HEADER_END,
+                actual: buffer.len(),
+            });
         }
 
+        let cgb_support: CgbSupport = buffer[CGB_FLAG_ADDR].into();
+
+        // newer cartridges use the last four bytes of the title for a manufacturer code
+        let manufacturer_code = &buffer[MANUFACTURER_CODE_ADDR..CGB_FLAG_ADDR];
+        let manufacturer_code = if cgb_support != CgbSupport::None
+            && manufacturer_code
+                .iter()
+                .all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit())
+        {
+            Some(read_string(manufacturer_code))
+        } else {
+            None
+        };
+
+        let title_end = if manufacturer_code.is_some() {
+            MANUFACTURER_CODE_ADDR
+        } else {
+            CGB_FLAG_ADDR
+        };
+        let title = read_string(&buffer[TITLE_ADDR..title_end]);
+
         let rom_banks = match buffer[ROM_SIZE_ADDR] {
             0x00 => 2,
             0x01 => 4,
@@ -45,7 +247,15 @@ impl From<&Vec<u8>> for CartridgeHeader {
             0x06 => 128,
             0x07 => 256,
             0x08 => 512,
-            _ => panic!("Unknown ROM size {}", buffer[ROM_SIZE_ADDR]),
+            0x52 => 72,
+            0x53 => 80,
+            0x54 => 96,
+            value => {
+                return Err(LoadError::InvalidHeaderField {
+                    field: "ROM size",
+                    value,
+                });
+            },
         };
 
         let ram_banks = match buffer[RAM_SIZE_ADDR] {
@@ -54,14 +264,121 @@ impl From<&Vec<u8>> for CartridgeHeader {
             0x03 => 4,
             0x04 => 16,
             0x05 => 8,
-            _ => panic!("Unknown RAM size {}", buffer[RAM_SIZE_ADDR]),
+            value => {
+                return Err(LoadError::InvalidHeaderField {
+                    field: "RAM size",
+                    value,
+                });
+            },
         };
 
-        CartridgeHeader {
+        let mut entry_point = [0; 4];
+        entry_point.copy_from_slice(&buffer[ENTRY_POINT_ADDR..LOGO_ADDR]);
+        let mut logo = [0; LOGO_SIZE];
+        logo.copy_from_slice(&buffer[LOGO_ADDR..TITLE_ADDR]);
+
+        Ok(CartridgeHeader {
+            entry_point,
+            logo,
             title,
-            mbc_type: buffer[MBC_TYPE_ADDR].into(),
+            manufacturer_code,
+            cgb_support,
+            new_licensee_code: read_string(&buffer[NEW_LICENSEE_CODE_ADDR..SGB_FLAG_ADDR]),
+            sgb_support: buffer[SGB_FLAG_ADDR] == 0x03,
+            cartridge_type: buffer[CARTRIDGE_TYPE_ADDR],
             rom_banks,
             ram_banks,
-        }
+            destination: buffer[DESTINATION_CODE_ADDR].into(),
+            old_licensee_code: buffer[OLD_LICENSEE_CODE_ADDR],
+            mask_rom_version: buffer[MASK_ROM_VERSION_ADDR],
+            header_checksum: Checksum {
+                expected: buffer[HEADER_CHECKSUM_ADDR],
+                computed: header_checksum(buffer),
+            },
+            global_checksum: Checksum {
+                expected: u16::from_be_bytes([
+                    buffer[GLOBAL_CHECKSUM_ADDR],
+                    buffer[GLOBAL_CHECKSUM_ADDR + 1],
+                ]),
+                computed: global_checksum(buffer),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header_buffer() -> Vec<u8> {
+        let mut buffer = vec![0; 0x8000];
+        buffer[ENTRY_POINT_ADDR..LOGO_ADDR].copy_from_slice(&[0x00, 0xC3, 0x50, 0x01]);
+        buffer[LOGO_ADDR..TITLE_ADDR].copy_from_slice(&NINTENDO_LOGO);
+        buffer[TITLE_ADDR..TITLE_ADDR + 7].copy_from_slice(b"POKEMON");
+        buffer[MANUFACTURER_CODE_ADDR..CGB_FLAG_ADDR].copy_from_slice(b"AAUE");
+        buffer[CGB_FLAG_ADDR] = 0x80;
+        buffer[NEW_LICENSEE_CODE_ADDR..SGB_FLAG_ADDR].copy_from_slice(b"01");
+        buffer[SGB_FLAG_ADDR] = 0x03;
+        buffer[CARTRIDGE_TYPE_ADDR] = 0x03;
+        buffer[ROM_SIZE_ADDR] = 0x00;
+        buffer[RAM_SIZE_ADDR] = 0x03;
+        buffer[DESTINATION_CODE_ADDR] = 0x01;
+        buffer[OLD_LICENSEE_CODE_ADDR] = USE_NEW_LICENSEE_CODE;
+        buffer[MASK_ROM_VERSION_ADDR] = 0x01;
+        buffer[HEADER_CHECKSUM_ADDR] = header_checksum(&buffer);
+        let [high, low] = global_checksum(&buffer).to_be_bytes();
+        buffer[GLOBAL_CHECKSUM_ADDR] = high;
+        buffer[GLOBAL_CHECKSUM_ADDR + 1] = low;
+
+        buffer
+    }
+
+    #[test]
+    fn test_parse_header() {
+        let header = CartridgeHeader::try_from(header_buffer().as_slice()).unwrap();
+
+        assert_eq!(header.entry_point, [0x00, 0xC3, 0x50, 0x01]);
+        assert_eq!(header.title, "POKEMON");
+        assert_eq!(header.manufacturer_code.as_deref(), Some("AAUE"));
+        assert_eq!(header.cgb_support, CgbSupport::Compatible);
+        assert!(header.sgb_support);
+        assert_eq!(header.cartridge_type_name(), "MBC1+RAM+BATTERY");
+        assert_eq!(header.rom_banks, 2);
+        assert_eq!(header.ram_banks, 4);
+        assert_eq!(header.destination, Destination::Overseas);
+        assert_eq!(header.licensee_code(), "01");
+        assert_eq!(header.mask_rom_version, 0x01);
+        assert!(header.logo_valid());
+        assert!(header.header_checksum.is_valid());
+        assert!(header.global_checksum.is_valid());
+        assert!(header.is_valid());
+    }
+
+    #[test]
+    fn test_invalid_checksums() {
+        let mut buffer = header_buffer();
+        buffer[TITLE_ADDR] = b'D';
+        buffer[0x4000] = 0x42;
+
+        let header = CartridgeHeader::try_from(buffer.as_slice()).unwrap();
+        assert_eq!(header.title, "DOKEMON");
+        assert!(!header.header_checksum.is_valid());
+        assert!(!header.global_checksum.is_valid());
+        assert!(!header.is_valid());
+    }
+
+    #[test]
+    fn test_old_title_layout() {
+        let mut buffer = vec![0; 0x8000];
+        buffer[TITLE_ADDR..CGB_FLAG_ADDR].copy_from_slice(b"CPU_INSTRS\0\0\0\0\0");
+        buffer[OLD_LICENSEE_CODE_ADDR] = 0x01;
+
+        let header = CartridgeHeader::try_from(buffer.as_slice()).unwrap();
+        assert_eq!(header.title, "CPU_INSTRS");
+        assert_eq!(header.manufacturer_code, None);
+        assert_eq!(header.cgb_support, CgbSupport::None);
+        assert_eq!(header.licensee_code(), "01");
+        assert_eq!(header.destination, Destination::Japan);
+        assert!(!header.logo_valid());
     }
 }
@@ -1,17 +1,20 @@
 mod alu;
 
+pub mod bus;
 pub mod instructions;
 pub mod interrupts;
 pub mod registers;
 
+use crate::disassembler::{Symbols, disassemble};
 use crate::emulator::ExecutionError;
-use crate::system::System;
+use crate::model::Model;
 
-use self::instructions::Instruction;
+use self::bus::Bus;
+use self::instructions::{ArithmeticOperand, Instruction};
 use self::registers::Registers;
 
 use std::fmt::Debug;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, trace};
 
 pub struct Cpu {
     pub registers: Registers,
@@ -46,36 +49,18 @@ impl Cpu {
         Cpu::new_from_registers(Registers::default())
     }
 
-    pub fn new(mmu: &mut System) -> Self {
-        Cpu::new_from_registers(Registers {
-            a: 0x01,
-            f: if mmu.read_byte(0x14D) == 0x00 {
-                0b1000_0000
-            } else {
-                0b1011_0000
-            },
-            b: 0x00,
-            c: 0x13,
-            d: 0x00,
-            e: 0xD8,
-            h: 0x01,
-            l: 0x4D,
-            w: 0x00,
-            z: 0x00,
-            pc: 0x0100,
-            sp: 0xfffe,
-            cc: false,
-        })
+    pub fn new<B: Bus>(mmu: &mut B, model: Model) -> Self {
+        Cpu::new_from_registers(model.post_boot_registers(mmu.read_byte(0x14D)))
     }
 
-    pub fn read_byte_pc(&mut self, mmu: &mut System) -> u8 {
-        let byte = mmu.read_byte(self.registers.pc);
+    pub fn read_byte_pc<B: Bus>(&mut self, mmu: &mut B) -> u8 {
+        let byte = mmu.fetch_byte(self.registers.pc);
         (self.registers.pc, _) = self.registers.pc.overflowing_add(1);
 
         byte
     }
 
-    pub fn step(&mut self, mmu: &mut System) -> Result<bool, ExecutionError> {
+    pub fn step<B: Bus>(&mut self, mmu: &mut B) -> Result<bool, ExecutionError> {
         if self.interrupt_enable_pending && !self.interrupt_enabled {
             self.interrupt_enabled = true;
             self.interrupt_enable_pending = false;
@@ -94,8 +79,76 @@ impl Cpu {
         }
     }
 
+    // Executes one M-cycle: the CPU's part of the current instruction, then the rest of the
+    // system and, once the current instruction completed, the fetch of the next one
+    pub fn cycle<B: Bus>(&mut self, mmu: &mut B) -> Result<bool, ExecutionError> {
+        let mut completed = false;
+        if !self.halted {
+            completed = self.step(mmu)?;
+        }
+
+        mmu.tick()?;
+
+        if mmu.interrupt_enable() & mmu.interrupt_flags() != 0 {
+            self.halted = false;
+        }
+
+        if !self.halted && completed {
+            if !matches!(self.current_instruction, Instruction::isr { .. }) {
+                self.trace_state(mmu);
+            }
+
+            self.generic_fetch(mmu)?;
+        }
+
+        Ok(completed)
+    }
+
+    pub fn trace_state<B: Bus>(&self, mmu: &B) {
+        trace!(
+            name: "cpu::state",
+            "{:?} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            self,
+            mmu.peek_byte(self.registers.pc),
+            mmu.peek_byte(self.registers.pc.wrapping_add(1)),
+            mmu.peek_byte(self.registers.pc.wrapping_add(2)),
+            mmu.peek_byte(self.registers.pc.wrapping_add(3)),
+        );
+        trace!(
+            name: "cpu::disassembly",
+            "{}",
+            disassemble(mmu, self.registers.pc, &Symbols::new())
+        );
+    }
+
+    // decodes the first instruction, all following ones are fetched while the previous one completes
+    pub fn prefetch<B: Bus>(&mut self, mmu: &mut B) {
+        let opcode = self.read_byte_pc(mmu);
+        self.current_instruction = Instruction::decode_instruction(opcode);
+    }
+
+    // Address of the prefetched instruction, only valid between two instructions. None if the
+    // next thing to execute is an interrupt service routine.
+    pub fn instruction_address(&self) -> Option<u16> {
+        match self.current_instruction {
+            Instruction::isr { .. } => None,
+            _ => Some(self.registers.pc.wrapping_sub(1)),
+        }
+    }
+
+    // LD B,B is used as a software breakpoint by test ROMs and debuggers
+    pub fn at_software_breakpoint(&self) -> bool {
+        matches!(
+            self.current_instruction,
+            Instruction::ld_r8_r8 {
+                operand_a: ArithmeticOperand::B,
+                operand_b: ArithmeticOperand::B,
+            }
+        )
+    }
+
     #[instrument(skip_all)]
-    pub fn generic_fetch(&mut self, mmu: &mut System) -> Result<(), ExecutionError> {
+    pub fn generic_fetch<B: Bus>(&mut self, mmu: &mut B) -> Result<(), ExecutionError> {
         if let Some(interrupt) = self.interrupt_check(mmu) {
             debug!(
                 "Executing interrupt service routing for interrupt {:?}",
@@ -0,0 +1,31 @@
+use crate::emulator::ExecutionError;
+
+// Everything the CPU needs from the rest of the system. Implemented by `System`, test harnesses,
+// alternate memory maps or instrumentation wrappers can provide their own.
+pub trait Bus {
+    fn read_byte(&mut self, address: u16) -> u8;
+    fn write_byte(&mut self, address: u16, value: u8);
+
+    // opcode and operand fetches, so instrumentation can tell them apart from data reads
+    fn fetch_byte(&mut self, address: u16) -> u8 {
+        self.read_byte(address)
+    }
+
+    // read without side effects, e.g. for tracing
+    fn peek_byte(&self, address: u16) -> u8;
+
+    fn interrupt_enable(&self) -> u8;
+    fn interrupt_flags(&self) -> u8;
+    fn set_interrupt_flags(&mut self, value: u8);
+
+    // ROM bank mapped at the address, only known to buses with a cartridge. Used to resolve
+    // banked symbols.
+    fn bank(&self, _address: u16) -> Option<usize> {
+        None
+    }
+
+    // called once per M-cycle after the CPU accessed the bus
+    fn tick(&mut self) -> Result<(), ExecutionError> {
+        Ok(())
+    }
+}
@@ -1,11 +1,10 @@
 use std::fmt::Debug;
 
-use crate::system::System;
 use crate::utils::bit_operations::extract_bits;
 use crate::utils::half_carry::half_carry_add_r8;
 use crate::utils::half_carry::half_carry_add_r8_3;
 
-use super::{Cpu, ExecutionError, interrupts::Interrupt};
+use super::{Cpu, ExecutionError, bus::Bus, interrupts::Interrupt};
 
 macro_rules! panic_execuction {
     () => {
@@ -609,7 +608,7 @@ impl Cpu {
         self.registers.w = ((self.registers.pc >> 8) as i16 + adj) as u8;
     }
 
-    pub(super) fn instruction_step(&mut self, mmu: &mut System) -> Result<bool, ExecutionError> {
+    pub(super) fn instruction_step<B: Bus>(&mut self, mmu: &mut B) -> Result<bool, ExecutionError> {
         match self.current_instruction {
             //special
             Instruction::nop => Ok(true),
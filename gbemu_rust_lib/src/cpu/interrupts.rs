@@ -1,7 +1,7 @@
-use crate::system::System;
 use crate::utils::bit_operations::bit;
 
 use super::Cpu;
+use super::bus::Bus;
 
 const JOYPAD_INTERRUPT_BIT: usize = 4;
 const SERIAL_INTERRUPT_BIT: usize = 3;
@@ -9,7 +9,7 @@ const TIMER_INTERRUPT_BIT: usize = 2;
 const LCD_INTERRUPT_BIT: usize = 1;
 const V_BLANK_INTERRUPT_BIT: usize = 0;
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Interrupt {
     VBlank,
     Lcd,
@@ -39,6 +39,18 @@ pub struct InterruptFlags {
     pub v_blank: bool,
 }
 
+impl InterruptFlags {
+    pub fn request(&mut self, interrupt: Interrupt) {
+        match interrupt {
+            Interrupt::Joypad => self.joypad = true,
+            Interrupt::Serial => self.serial = true,
+            Interrupt::Timer => self.timer = true,
+            Interrupt::Lcd => self.lcd = true,
+            Interrupt::VBlank => self.v_blank = true,
+        }
+    }
+}
+
 impl From<u8> for InterruptFlags {
     fn from(value: u8) -> Self {
         Self {
@@ -80,59 +92,42 @@ impl From<InterruptFlags> for u8 {
 }
 
 impl Cpu {
-    pub fn request_interrupt(&mut self, mmu: &mut System, interrupt: Interrupt) {
-        match interrupt {
-            Interrupt::Joypad => mmu.io.interrupt_flags.joypad = true,
-            Interrupt::Serial => mmu.io.interrupt_flags.serial = true,
-            Interrupt::Timer => mmu.io.interrupt_flags.timer = true,
-            Interrupt::Lcd => mmu.io.interrupt_flags.lcd = true,
-            Interrupt::VBlank => mmu.io.interrupt_flags.v_blank = true,
-        }
+    pub fn request_interrupt<B: Bus>(&mut self, mmu: &mut B, interrupt: Interrupt) {
+        let mut flags: InterruptFlags = mmu.interrupt_flags().into();
+        flags.request(interrupt);
+        mmu.set_interrupt_flags(flags.into());
     }
 
-    pub fn interrupt_check(&mut self, mmu: &mut System) -> Option<Interrupt> {
+    pub fn interrupt_check<B: Bus>(&mut self, mmu: &mut B) -> Option<Interrupt> {
         if !self.interrupt_enabled {
             return None;
         }
 
-        if (mmu.io.interrupt_enable & (1 << V_BLANK_INTERRUPT_BIT)) > 0
-            && mmu.io.interrupt_flags.v_blank
-        {
-            self.interrupt_enabled = false;
-            mmu.io.interrupt_flags.v_blank = false;
-            return Some(Interrupt::VBlank);
-        }
+        let interrupt_enable = mmu.interrupt_enable();
+        let mut flags: InterruptFlags = mmu.interrupt_flags().into();
 
-        if (mmu.io.interrupt_enable & (1 << LCD_INTERRUPT_BIT)) > 0 && mmu.io.interrupt_flags.lcd {
-            self.interrupt_enabled = false;
-            mmu.io.interrupt_flags.lcd = false;
-            return Some(Interrupt::Lcd);
-        }
-
-        if (mmu.io.interrupt_enable & (1 << TIMER_INTERRUPT_BIT)) > 0
-            && mmu.io.interrupt_flags.timer
-        {
-            self.interrupt_enabled = false;
-            mmu.io.interrupt_flags.timer = false;
-            return Some(Interrupt::Timer);
-        }
-
-        if (mmu.io.interrupt_enable & (1 << SERIAL_INTERRUPT_BIT)) > 0
-            && mmu.io.interrupt_flags.serial
-        {
-            self.interrupt_enabled = false;
-            mmu.io.interrupt_flags.serial = false;
-            return Some(Interrupt::Serial);
-        }
+        let interrupt = if (interrupt_enable & (1 << V_BLANK_INTERRUPT_BIT)) > 0 && flags.v_blank {
+            flags.v_blank = false;
+            Interrupt::VBlank
+        } else if (interrupt_enable & (1 << LCD_INTERRUPT_BIT)) > 0 && flags.lcd {
+            flags.lcd = false;
+            Interrupt::Lcd
+        } else if (interrupt_enable & (1 << TIMER_INTERRUPT_BIT)) > 0 && flags.timer {
+            flags.timer = false;
+            Interrupt::Timer
+        } else if (interrupt_enable & (1 << SERIAL_INTERRUPT_BIT)) > 0 && flags.serial {
+            flags.serial = false;
+            Interrupt::Serial
+        } else if (interrupt_enable & (1 << JOYPAD_INTERRUPT_BIT)) > 0 && flags.joypad {
+            flags.joypad = false;
+            Interrupt::Joypad
+        } else {
+            return None;
+        };
 
-        if (mmu.io.interrupt_enable & (1 << JOYPAD_INTERRUPT_BIT)) > 0
-            && mmu.io.interrupt_flags.joypad
-        {
-            self.interrupt_enabled = false;
-            mmu.io.interrupt_flags.joypad = false;
-            return Some(Interrupt::Joypad);
-        }
+        self.interrupt_enabled = false;
+        mmu.set_interrupt_flags(flags.into());
 
-        None
+        Some(interrupt)
     }
 }
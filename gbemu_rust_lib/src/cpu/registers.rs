@@ -27,7 +27,7 @@ pub enum Register16 {
     HL,
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Clone)]
 pub struct Registers {
     pub a: u8,
     pub f: u8,
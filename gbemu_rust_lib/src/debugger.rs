@@ -0,0 +1,709 @@
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+use crate::cpu::bus::Bus;
+use crate::cpu::instructions::Instruction;
+use crate::cpu::interrupts::Interrupt;
+use crate::cpu::registers::Registers;
+use crate::disassembler::{DisassembledInstruction, Symbols, disassemble_range};
+use crate::emulator::{Emulator, ExecutionError};
+use crate::graphics::LCD_HEIGHT;
+use crate::memory::V_RAM_ADDR;
+use crate::system::System;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DebugRegister {
+    A,
+    F,
+    B,
+    C,
+    D,
+    E,
+    H,
+    L,
+    AF,
+    BC,
+    DE,
+    HL,
+    SP,
+    PC,
+}
+
+impl DebugRegister {
+    pub fn value(&self, registers: &Registers) -> u16 {
+        match self {
+            DebugRegister::A => registers.a as u16,
+            DebugRegister::F => registers.f as u16,
+            DebugRegister::B => registers.b as u16,
+            DebugRegister::C => registers.c as u16,
+            DebugRegister::D => registers.d as u16,
+            DebugRegister::E => registers.e as u16,
+            DebugRegister::H => registers.h as u16,
+            DebugRegister::L => registers.l as u16,
+            DebugRegister::AF => registers.get_af(),
+            DebugRegister::BC => registers.get_bc(),
+            DebugRegister::DE => registers.get_de(),
+            DebugRegister::HL => registers.get_hl(),
+            DebugRegister::SP => registers.sp,
+            // PC already points behind the prefetched opcode
+            DebugRegister::PC => registers.pc.wrapping_sub(1),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Condition {
+    pub register: DebugRegister,
+    pub comparison: Comparison,
+    pub value: u16,
+}
+
+impl Condition {
+    pub fn new(register: DebugRegister, comparison: Comparison, value: u16) -> Self {
+        Self {
+            register,
+            comparison,
+            value,
+        }
+    }
+
+    pub fn matches(&self, registers: &Registers) -> bool {
+        let value = self.register.value(registers);
+        match self.comparison {
+            Comparison::Equal => value == self.value,
+            Comparison::NotEqual => value != self.value,
+            Comparison::Less => value < self.value,
+            Comparison::LessEqual => value <= self.value,
+            Comparison::Greater => value > self.value,
+            Comparison::GreaterEqual => value >= self.value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Breakpoint {
+    pub address: u16,
+    // only break if this ROM bank is mapped, ignored outside of the cartridge ROM
+    pub bank: Option<usize>,
+    // all conditions have to match
+    pub conditions: Vec<Condition>,
+    pub enabled: bool,
+}
+
+impl Breakpoint {
+    pub fn new(address: u16) -> Self {
+        Self {
+            address,
+            bank: None,
+            conditions: vec![],
+            enabled: true,
+        }
+    }
+
+    pub fn in_bank(mut self, bank: usize) -> Self {
+        self.bank = Some(bank);
+        self
+    }
+
+    pub fn with_condition(mut self, condition: Condition) -> Self {
+        self.conditions.push(condition);
+        self
+    }
+
+    fn matches(&self, address: u16, system: &System, registers: &Registers) -> bool {
+        self.enabled
+            && self.address == address
+            && self
+                .bank
+                .is_none_or(|bank| address >= V_RAM_ADDR || system.rom_bank(address) == bank)
+            && self
+                .conditions
+                .iter()
+                .all(|condition| condition.matches(registers))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+    Execute,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<u16>,
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+    pub enabled: bool,
+}
+
+impl Watchpoint {
+    pub fn new(range: RangeInclusive<u16>, read: bool, write: bool, execute: bool) -> Self {
+        Self {
+            range,
+            read,
+            write,
+            execute,
+            enabled: true,
+        }
+    }
+
+    fn matches(&self, address: u16, access: Access) -> bool {
+        self.enabled
+            && self.range.contains(&address)
+            && match access {
+                Access::Read => self.read,
+                Access::Write => self.write,
+                Access::Execute => self.execute,
+            }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunTarget {
+    // only stop at breakpoints and watchpoints
+    Breakpoint,
+    // start of the next V-Blank
+    Frame,
+    Scanline,
+    Interrupt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    Breakpoint {
+        id: usize,
+        address: u16,
+    },
+    Watchpoint {
+        id: usize,
+        address: u16,
+        value: u8,
+        access: Access,
+    },
+    Step,
+    Frame,
+    Scanline {
+        line: u8,
+    },
+    Interrupt {
+        interrupt: Interrupt,
+    },
+    CycleLimit,
+}
+
+#[derive(Default)]
+pub struct Debugger {
+    next_id: usize,
+    breakpoints: BTreeMap<usize, Breakpoint>,
+    watchpoints: BTreeMap<usize, Watchpoint>,
+    pub symbols: Symbols,
+}
+
+impl Debugger {
+    fn next_id(&mut self) -> usize {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) -> usize {
+        let id = self.next_id();
+        self.breakpoints.insert(id, breakpoint);
+        id
+    }
+
+    pub fn remove_breakpoint(&mut self, id: usize) -> Option<Breakpoint> {
+        self.breakpoints.remove(&id)
+    }
+
+    pub fn breakpoint_mut(&mut self, id: usize) -> Option<&mut Breakpoint> {
+        self.breakpoints.get_mut(&id)
+    }
+
+    pub fn breakpoints(&self) -> impl Iterator<Item = (usize, &Breakpoint)> {
+        self.breakpoints
+            .iter()
+            .map(|(id, breakpoint)| (*id, breakpoint))
+    }
+
+    pub fn add_watchpoint(&mut self, watchpoint: Watchpoint) -> usize {
+        let id = self.next_id();
+        self.watchpoints.insert(id, watchpoint);
+        id
+    }
+
+    pub fn remove_watchpoint(&mut self, id: usize) -> Option<Watchpoint> {
+        self.watchpoints.remove(&id)
+    }
+
+    pub fn watchpoint_mut(&mut self, id: usize) -> Option<&mut Watchpoint> {
+        self.watchpoints.get_mut(&id)
+    }
+
+    pub fn watchpoints(&self) -> impl Iterator<Item = (usize, &Watchpoint)> {
+        self.watchpoints
+            .iter()
+            .map(|(id, watchpoint)| (*id, watchpoint))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.breakpoints.is_empty() && self.watchpoints.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.breakpoints.clear();
+        self.watchpoints.clear();
+    }
+
+    fn watchpoint_hit(&self, address: u16, value: u8, access: Access) -> Option<StopReason> {
+        self.watchpoints
+            .iter()
+            .find(|(_, watchpoint)| watchpoint.matches(address, access))
+            .map(|(id, _)| StopReason::Watchpoint {
+                id: *id,
+                address,
+                value,
+                access,
+            })
+    }
+}
+
+// Wraps the system to check all CPU accesses against the watchpoints
+struct WatchBus<'a> {
+    system: &'a mut System,
+    debugger: &'a Debugger,
+    hit: Option<StopReason>,
+}
+
+impl WatchBus<'_> {
+    fn check(&mut self, address: u16, value: u8, access: Access) {
+        if self.hit.is_none() {
+            self.hit = self.debugger.watchpoint_hit(address, value, access);
+        }
+    }
+}
+
+impl Bus for WatchBus<'_> {
+    fn read_byte(&mut self, address: u16) -> u8 {
+        let value = self.system.read_byte(address);
+        self.check(address, value, Access::Read);
+        value
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        self.system.write_byte(address, value);
+        self.check(address, value, Access::Write);
+    }
+
+    // fetches are covered by execute watchpoints
+    fn fetch_byte(&mut self, address: u16) -> u8 {
+        self.system.read_byte(address)
+    }
+
+    fn peek_byte(&self, address: u16) -> u8 {
+        self.system.peek_byte(address)
+    }
+
+    fn interrupt_enable(&self) -> u8 {
+        self.system.interrupt_enable()
+    }
+
+    fn interrupt_flags(&self) -> u8 {
+        self.system.interrupt_flags()
+    }
+
+    fn set_interrupt_flags(&mut self, value: u8) {
+        self.system.set_interrupt_flags(value);
+    }
+
+    fn bank(&self, address: u16) -> Option<usize> {
+        self.system.bank(address)
+    }
+
+    fn tick(&mut self) -> Result<(), ExecutionError> {
+        self.system.tick()
+    }
+}
+
+struct CycleResult {
+    // the instruction which completed in this cycle
+    executed: Option<Instruction>,
+    watchpoint: Option<StopReason>,
+}
+
+// What happened since the last instruction boundary
+struct Progress {
+    executed: Instruction,
+    line: Option<u8>,
+    v_blank: bool,
+}
+
+impl Emulator {
+    fn debug_step(&mut self) -> Result<CycleResult, ExecutionError> {
+        if self.system.oam_transfer {
+            self.system.oam_transfer_step();
+        }
+
+        let executing = self.cpu.current_instruction;
+        let mut bus = WatchBus {
+            system: &mut self.system,
+            debugger: &self.debugger,
+            hit: None,
+        };
+        let completed = self.cpu.cycle(&mut bus);
+        let watchpoint = bus.hit;
+        let completed = completed.inspect_err(|_| self.trace.dump(&self.debugger.symbols))?;
+        self.trace.tick(&self.cpu, &self.system, completed);
+
+        Ok(CycleResult {
+            executed: (completed && !self.cpu.halted).then_some(executing),
+            watchpoint,
+        })
+    }
+
+    fn breakpoint_hit(&self) -> Option<StopReason> {
+        let address = self.cpu.instruction_address()?;
+
+        let value = self.system.peek_byte(address);
+        if let Some(reason) = self
+            .debugger
+            .watchpoint_hit(address, value, Access::Execute)
+        {
+            return Some(reason);
+        }
+
+        self.debugger
+            .breakpoints
+            .iter()
+            .find(|(_, breakpoint)| breakpoint.matches(address, &self.system, &self.cpu.registers))
+            .map(|(id, _)| StopReason::Breakpoint { id: *id, address })
+    }
+
+    // Runs until breakpoints, watchpoints or `stop` match. The emulator always stops between two
+    // instructions, accesses are reported once the accessing instruction completed.
+    fn run_debug<F>(&mut self, max_cycles: u64, mut stop: F) -> Result<StopReason, ExecutionError>
+    where
+        F: FnMut(&Emulator, &Progress) -> Option<StopReason>,
+    {
+        let mut watchpoint = None;
+        let mut ly = self.system.graphics.registers.get_lcd_ly();
+        let mut line = None;
+        let mut v_blank = false;
+
+        for _ in 0..max_cycles {
+            let result = self.debug_step()?;
+            watchpoint = watchpoint.or(result.watchpoint);
+
+            let new_ly = self.system.graphics.registers.get_lcd_ly();
+            if new_ly != ly {
+                ly = new_ly;
+                line = Some(ly);
+                v_blank |= ly as usize == LCD_HEIGHT;
+            }
+
+            let Some(executed) = result.executed else {
+                continue;
+            };
+
+            if let Some(reason) = watchpoint.or_else(|| self.breakpoint_hit()) {
+                self.trace.dump(&self.debugger.symbols);
+                return Ok(reason);
+            }
+
+            let progress = Progress {
+                executed,
+                line: line.take(),
+                v_blank,
+            };
+            v_blank = false;
+
+            if let Some(reason) = stop(self, &progress) {
+                return Ok(reason);
+            }
+        }
+
+        Ok(StopReason::CycleLimit)
+    }
+
+    pub fn run_until(
+        &mut self,
+        target: RunTarget,
+        max_cycles: u64,
+    ) -> Result<StopReason, ExecutionError> {
+        self.run_debug(max_cycles, |emu, progress| match target {
+            RunTarget::Breakpoint => None,
+            RunTarget::Frame => progress.v_blank.then_some(StopReason::Frame),
+            RunTarget::Scanline => progress.line.map(|line| StopReason::Scanline { line }),
+            RunTarget::Interrupt => match emu.cpu.current_instruction {
+                Instruction::isr { interrupt } => Some(StopReason::Interrupt { interrupt }),
+                _ => None,
+            },
+        })
+    }
+
+    // Disassembles from the next instruction on, labels come from the debugger symbols
+    pub fn disassemble(&self, count: usize) -> Vec<DisassembledInstruction> {
+        let address = self
+            .cpu
+            .instruction_address()
+            .unwrap_or(self.cpu.registers.pc);
+
+        disassemble_range(&self.system, address, count, &self.debugger.symbols)
+    }
+
+    pub fn step_into(&mut self, max_cycles: u64) -> Result<StopReason, ExecutionError> {
+        self.run_debug(max_cycles, |_, _| Some(StopReason::Step))
+    }
+
+    // Runs calls and restarts until they returned
+    pub fn step_over(&mut self, max_cycles: u64) -> Result<StopReason, ExecutionError> {
+        let sp = self.cpu.registers.sp;
+        let return_address = match self.cpu.current_instruction {
+            Instruction::call_n16 | Instruction::call_cond_n16 { .. } => {
+                self.cpu.registers.pc.wrapping_add(2)
+            },
+            Instruction::rst_tgt3 { .. } => self.cpu.registers.pc,
+            _ => return self.step_into(max_cycles),
+        };
+
+        self.run_debug(max_cycles, |emu, _| {
+            (emu.cpu.instruction_address() == Some(return_address) && emu.cpu.registers.sp == sp)
+                .then_some(StopReason::Step)
+        })
+    }
+
+    // Runs until the current function returned
+    pub fn step_out(&mut self, max_cycles: u64) -> Result<StopReason, ExecutionError> {
+        let sp = self.cpu.registers.sp;
+
+        self.run_debug(max_cycles, |emu, progress| {
+            let returned = matches!(
+                progress.executed,
+                Instruction::ret | Instruction::reti | Instruction::ret_cond { .. }
+            ) && emu.cpu.registers.sp > sp;
+
+            returned.then_some(StopReason::Step)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{setup_default_logger, test_emulator};
+
+    use super::*;
+
+    const MAX_CYCLES: u64 = 100_000;
+
+    #[test]
+    fn test_breakpoint() {
+        let _guard = setup_default_logger();
+
+        // INC A; JR -3
+        let mut emu = test_emulator(2, &[(0x0100, &[0x3C, 0x18, 0xFD])]);
+        let id =
+            emu.debugger
+                .add_breakpoint(Breakpoint::new(0x0100).with_condition(Condition::new(
+                    DebugRegister::A,
+                    Comparison::Equal,
+                    0x05,
+                )));
+
+        emu.cpu.registers.a = 0;
+        assert_eq!(
+            emu.run_until(RunTarget::Breakpoint, MAX_CYCLES).unwrap(),
+            StopReason::Breakpoint {
+                id,
+                address: 0x0100
+            }
+        );
+        assert_eq!(emu.cpu.registers.a, 0x05);
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0100));
+
+        emu.debugger.breakpoint_mut(id).unwrap().enabled = false;
+        assert_eq!(
+            emu.run_until(RunTarget::Breakpoint, 100).unwrap(),
+            StopReason::CycleLimit
+        );
+    }
+
+    #[test]
+    fn test_bank_breakpoint() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(
+            4,
+            &[
+                // JP 0x4000
+                (0x0100, &[0xC3, 0x00, 0x40]),
+                // LD A, 2; LD (0x2000), A; JP 0x4000
+                (0x4000, &[0x3E, 0x02, 0xEA, 0x00, 0x20, 0xC3, 0x00, 0x40]),
+                // JR -2
+                (0x8000, &[0x18, 0xFE]),
+            ],
+        );
+        let id = emu
+            .debugger
+            .add_breakpoint(Breakpoint::new(0x4000).in_bank(2));
+
+        assert_eq!(
+            emu.run_until(RunTarget::Breakpoint, MAX_CYCLES).unwrap(),
+            StopReason::Breakpoint {
+                id,
+                address: 0x4000
+            }
+        );
+        assert_eq!(emu.system.rom_bank(0x4000), 2);
+    }
+
+    #[test]
+    fn test_watchpoint() {
+        let _guard = setup_default_logger();
+
+        // LD HL, 0xC000; NOP; INC (HL); JR -3
+        let mut emu = test_emulator(2, &[(0x0100, &[0x21, 0x00, 0xC0, 0x00, 0x34, 0x18, 0xFD])]);
+        let value = emu.system.read_byte(0xC000).wrapping_add(1);
+        let id = emu
+            .debugger
+            .add_watchpoint(Watchpoint::new(0xC000..=0xC0FF, false, true, false));
+
+        assert_eq!(
+            emu.run_until(RunTarget::Breakpoint, MAX_CYCLES).unwrap(),
+            StopReason::Watchpoint {
+                id,
+                address: 0xC000,
+                value,
+                access: Access::Write
+            }
+        );
+        // stopped after INC (HL)
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0105));
+
+        emu.debugger.remove_watchpoint(id);
+        let id = emu
+            .debugger
+            .add_watchpoint(Watchpoint::new(0x0104..=0x0104, false, false, true));
+        assert_eq!(
+            emu.run_until(RunTarget::Breakpoint, MAX_CYCLES).unwrap(),
+            StopReason::Watchpoint {
+                id,
+                address: 0x0104,
+                value: 0x34,
+                access: Access::Execute
+            }
+        );
+    }
+
+    #[test]
+    fn test_read_watchpoint_ignores_fetches() {
+        let _guard = setup_default_logger();
+
+        // INC A; JR -3
+        let mut emu = test_emulator(2, &[(0x0100, &[0x3C, 0x18, 0xFD])]);
+        emu.debugger
+            .add_watchpoint(Watchpoint::new(0x0100..=0x0100, true, false, false));
+
+        assert_eq!(
+            emu.run_until(RunTarget::Breakpoint, 1000).unwrap(),
+            StopReason::CycleLimit
+        );
+    }
+
+    #[test]
+    fn test_stepping() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(
+            2,
+            &[
+                // CALL 0x0200; NOP
+                (0x0100, &[0xCD, 0x00, 0x02, 0x00]),
+                // NOP; CALL 0x0300; RET
+                (0x0200, &[0x00, 0xCD, 0x00, 0x03, 0xC9]),
+                // RET
+                (0x0300, &[0xC9]),
+            ],
+        );
+
+        assert_eq!(emu.step_into(MAX_CYCLES).unwrap(), StopReason::Step);
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0200));
+        assert_eq!(emu.step_into(MAX_CYCLES).unwrap(), StopReason::Step);
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0201));
+
+        assert_eq!(emu.step_over(MAX_CYCLES).unwrap(), StopReason::Step);
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0204));
+
+        assert_eq!(emu.step_out(MAX_CYCLES).unwrap(), StopReason::Step);
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0103));
+    }
+
+    #[test]
+    fn test_step_out_of_nested_call() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(
+            2,
+            &[
+                // CALL 0x0200; NOP
+                (0x0100, &[0xCD, 0x00, 0x02, 0x00]),
+                // CALL 0x0300; RET
+                (0x0200, &[0xCD, 0x00, 0x03, 0xC9]),
+                // RET
+                (0x0300, &[0xC9]),
+            ],
+        );
+
+        emu.step_into(MAX_CYCLES).unwrap();
+        assert_eq!(emu.step_out(MAX_CYCLES).unwrap(), StopReason::Step);
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0103));
+    }
+
+    #[test]
+    fn test_run_until() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(
+            2,
+            &[
+                // LD A, 1; LDH (IE), A; EI; HALT; JR -3
+                (0x0100, &[0x3E, 0x01, 0xE0, 0xFF, 0xFB, 0x76, 0x18, 0xFD]),
+                // RETI
+                (0x0040, &[0xD9]),
+            ],
+        );
+
+        assert_eq!(
+            emu.run_until(RunTarget::Scanline, MAX_CYCLES).unwrap(),
+            StopReason::Scanline {
+                line: emu.system.graphics.registers.get_lcd_ly()
+            }
+        );
+
+        assert_eq!(
+            emu.run_until(RunTarget::Interrupt, MAX_CYCLES).unwrap(),
+            StopReason::Interrupt {
+                interrupt: Interrupt::VBlank
+            }
+        );
+        assert_eq!(emu.cpu.instruction_address(), None);
+
+        assert_eq!(
+            emu.run_until(RunTarget::Frame, MAX_CYCLES).unwrap(),
+            StopReason::Frame
+        );
+        // the halted CPU only stops once the V-Blank interrupt woke it up
+        assert!(emu.system.graphics.registers.get_lcd_ly() as usize >= LCD_HEIGHT);
+    }
+}
@@ -0,0 +1,567 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::path::Path;
+
+use crate::cpu::bus::Bus;
+use crate::cpu::instructions::{
+    ArithmeticOperand, ArithmeticOperand16, Condition, Instruction, MemoryOperand16, StackOperand16,
+};
+use crate::memory::{ROM_BANK_X_ADDR, V_RAM_ADDR};
+
+#[derive(Debug)]
+pub enum SymbolError {
+    Io(std::io::Error),
+    InvalidLine { line: usize, content: String },
+}
+
+impl Display for SymbolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "Could not read symbol file: {}", err),
+            Self::InvalidLine { line, content } => {
+                write!(f, "Invalid symbol on line {}: '{}'", line, content)
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Symbol {
+    bank: usize,
+    name: String,
+}
+
+// Labels of a `.sym` file as written by RGBDS and no$gmb
+#[derive(Debug, Clone, Default)]
+pub struct Symbols {
+    symbols: BTreeMap<u16, Vec<Symbol>>,
+}
+
+impl Symbols {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn load(path: &Path) -> Result<Self, SymbolError> {
+        let content = std::fs::read_to_string(path).map_err(SymbolError::Io)?;
+        Self::parse(&content)
+    }
+
+    // one `BB:AAAA Name` entry per line, `;` starts a comment and `[section]` headers are skipped
+    pub fn parse(content: &str) -> Result<Self, SymbolError> {
+        let mut symbols = Self::new();
+
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with('[') {
+                continue;
+            }
+
+            let invalid = || SymbolError::InvalidLine {
+                line: index + 1,
+                content: line.to_owned(),
+            };
+
+            let (location, name) = line.split_once(char::is_whitespace).ok_or_else(invalid)?;
+            let (bank, address) = location.split_once(':').ok_or_else(invalid)?;
+            let bank = usize::from_str_radix(bank, 16).map_err(|_| invalid())?;
+            let address = u16::from_str_radix(address, 16).map_err(|_| invalid())?;
+
+            symbols.insert(bank, address, name.trim());
+        }
+
+        Ok(symbols)
+    }
+
+    pub fn insert(&mut self, bank: usize, address: u16, name: &str) {
+        self.symbols.entry(address).or_default().push(Symbol {
+            bank,
+            name: name.to_owned(),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    // The bank is only compared for switchable ROM, everything else takes the first label
+    pub fn label(&self, address: u16, bank: Option<usize>) -> Option<&str> {
+        let symbols = self.symbols.get(&address)?;
+
+        let symbol = match bank {
+            Some(bank) if (ROM_BANK_X_ADDR..V_RAM_ADDR).contains(&address) => {
+                symbols.iter().find(|symbol| symbol.bank == bank)
+            },
+            _ => symbols.first(),
+        };
+
+        symbol.map(|symbol| symbol.name.as_str())
+    }
+
+    // Label of the current mapping of the address on the bus
+    pub fn lookup<B: Bus>(&self, bus: &B, address: u16) -> Option<&str> {
+        self.label(address, bus.bank(address))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    pub address: u16,
+    pub bytes: Vec<u8>,
+    // RGBDS syntax with resolved immediates and labels
+    pub text: String,
+    // label of the instruction itself
+    pub label: Option<String>,
+    // destination of jumps, calls and restarts
+    pub target: Option<u16>,
+}
+
+impl DisassembledInstruction {
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    pub fn next_address(&self) -> u16 {
+        self.address.wrapping_add(self.bytes.len() as u16)
+    }
+}
+
+impl Display for DisassembledInstruction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let bytes = self
+            .bytes
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        write!(f, "{:04X}: {:<8}  {}", self.address, bytes, self.text)
+    }
+}
+
+fn r8(operand: ArithmeticOperand) -> &'static str {
+    match operand {
+        ArithmeticOperand::B => "b",
+        ArithmeticOperand::C => "c",
+        ArithmeticOperand::D => "d",
+        ArithmeticOperand::E => "e",
+        ArithmeticOperand::H => "h",
+        ArithmeticOperand::L => "l",
+        ArithmeticOperand::IND_HL => "[hl]",
+        ArithmeticOperand::A => "a",
+    }
+}
+
+fn r16(operand: ArithmeticOperand16) -> &'static str {
+    match operand {
+        ArithmeticOperand16::BC => "bc",
+        ArithmeticOperand16::DE => "de",
+        ArithmeticOperand16::HL => "hl",
+        ArithmeticOperand16::SP => "sp",
+    }
+}
+
+fn r16mem(operand: MemoryOperand16) -> &'static str {
+    match operand {
+        MemoryOperand16::BC => "[bc]",
+        MemoryOperand16::DE => "[de]",
+        MemoryOperand16::HLI => "[hl+]",
+        MemoryOperand16::HLD => "[hl-]",
+    }
+}
+
+fn r16stk(operand: StackOperand16) -> &'static str {
+    match operand {
+        StackOperand16::BC => "bc",
+        StackOperand16::DE => "de",
+        StackOperand16::HL => "hl",
+        StackOperand16::AF => "af",
+    }
+}
+
+fn cond(condition: Condition) -> &'static str {
+    match condition {
+        Condition::NZ => "nz",
+        Condition::Z => "z",
+        Condition::NC => "nc",
+        Condition::C => "c",
+    }
+}
+
+fn signed(offset: i8) -> String {
+    if offset < 0 {
+        format!("-{}", offset.unsigned_abs())
+    } else {
+        format!("+{}", offset)
+    }
+}
+
+// Reads the remaining bytes of an instruction, the opcode is already consumed
+struct Operands<'a, B: Bus> {
+    bus: &'a B,
+    symbols: &'a Symbols,
+    address: u16,
+    bytes: Vec<u8>,
+}
+
+impl<B: Bus> Operands<'_, B> {
+    fn n8(&mut self) -> u8 {
+        let value = self
+            .bus
+            .peek_byte(self.address.wrapping_add(self.bytes.len() as u16));
+        self.bytes.push(value);
+        value
+    }
+
+    fn n16(&mut self) -> u16 {
+        let low = self.n8() as u16;
+        let high = self.n8() as u16;
+        (high << 8) | low
+    }
+
+    fn address(&self, address: u16) -> String {
+        match self.symbols.lookup(self.bus, address) {
+            Some(label) => label.to_owned(),
+            None => format!("${:04X}", address),
+        }
+    }
+
+    fn relative_target(&mut self) -> u16 {
+        let offset = self.n8() as i8;
+        self.address
+            .wrapping_add(self.bytes.len() as u16)
+            .wrapping_add(offset as u16)
+    }
+}
+
+pub fn disassemble<B: Bus>(bus: &B, address: u16, symbols: &Symbols) -> DisassembledInstruction {
+    let opcode = bus.peek_byte(address);
+    let mut operands = Operands {
+        bus,
+        symbols,
+        address,
+        bytes: vec![opcode],
+    };
+    let mut target = None;
+
+    let text = match Instruction::decode_instruction(opcode) {
+        Instruction::nop => "nop".to_owned(),
+        // STOP is followed by a padding byte
+        Instruction::stop => {
+            operands.n8();
+            "stop".to_owned()
+        },
+        Instruction::halt => "halt".to_owned(),
+        Instruction::di => "di".to_owned(),
+        Instruction::ei => "ei".to_owned(),
+
+        Instruction::add_a_r8 { operand } => format!("add a, {}", r8(operand)),
+        Instruction::adc_a_r8 { operand } => format!("adc a, {}", r8(operand)),
+        Instruction::sub_a_r8 { operand } => format!("sub a, {}", r8(operand)),
+        Instruction::sbc_a_r8 { operand } => format!("sbc a, {}", r8(operand)),
+        Instruction::and_a_r8 { operand } => format!("and a, {}", r8(operand)),
+        Instruction::xor_a_r8 { operand } => format!("xor a, {}", r8(operand)),
+        Instruction::or_a_r8 { operand } => format!("or a, {}", r8(operand)),
+        Instruction::cp_a_r8 { operand } => format!("cp a, {}", r8(operand)),
+        Instruction::inc_r8 { operand } => format!("inc {}", r8(operand)),
+        Instruction::dec_r8 { operand } => format!("dec {}", r8(operand)),
+        Instruction::add_a_n8 => format!("add a, ${:02X}", operands.n8()),
+        Instruction::adc_a_n8 => format!("adc a, ${:02X}", operands.n8()),
+        Instruction::sub_a_n8 => format!("sub a, ${:02X}", operands.n8()),
+        Instruction::sbc_a_n8 => format!("sbc a, ${:02X}", operands.n8()),
+        Instruction::and_a_n8 => format!("and a, ${:02X}", operands.n8()),
+        Instruction::xor_a_n8 => format!("xor a, ${:02X}", operands.n8()),
+        Instruction::or_a_n8 => format!("or a, ${:02X}", operands.n8()),
+        Instruction::cp_a_n8 => format!("cp a, ${:02X}", operands.n8()),
+        Instruction::cpl => "cpl".to_owned(),
+        Instruction::daa => "daa".to_owned(),
+
+        Instruction::rlca => "rlca".to_owned(),
+        Instruction::rrca => "rrca".to_owned(),
+        Instruction::rla => "rla".to_owned(),
+        Instruction::rra => "rra".to_owned(),
+
+        Instruction::scf => "scf".to_owned(),
+        Instruction::ccf => "ccf".to_owned(),
+
+        Instruction::inc_r16 { operand } => format!("inc {}", r16(operand)),
+        Instruction::dec_r16 { operand } => format!("dec {}", r16(operand)),
+        Instruction::add_hl_r16 { operand } => format!("add hl, {}", r16(operand)),
+        Instruction::add_sp_i8 => format!("add sp, {}", operands.n8() as i8),
+
+        Instruction::prefix => match Instruction::decode_prefix_instruction(operands.n8()) {
+            Instruction::rlc_r8 { operand } => format!("rlc {}", r8(operand)),
+            Instruction::rrc_r8 { operand } => format!("rrc {}", r8(operand)),
+            Instruction::rl_r8 { operand } => format!("rl {}", r8(operand)),
+            Instruction::rr_r8 { operand } => format!("rr {}", r8(operand)),
+            Instruction::sla_r8 { operand } => format!("sla {}", r8(operand)),
+            Instruction::sra_r8 { operand } => format!("sra {}", r8(operand)),
+            Instruction::swap_r8 { operand } => format!("swap {}", r8(operand)),
+            Instruction::srl_r8 { operand } => format!("srl {}", r8(operand)),
+            Instruction::bit_b3_r8 { index, operand } => format!("bit {}, {}", index, r8(operand)),
+            Instruction::res_b3_r8 { index, operand } => format!("res {}, {}", index, r8(operand)),
+            Instruction::set_b3_r8 { index, operand } => format!("set {}, {}", index, r8(operand)),
+            instruction => unreachable!("{:?} is not a prefix instruction", instruction),
+        },
+
+        Instruction::ld_r8_n8 { operand } => format!("ld {}, ${:02X}", r8(operand), operands.n8()),
+        Instruction::ld_r8_r8 {
+            operand_a,
+            operand_b,
+        } => format!("ld {}, {}", r8(operand_a), r8(operand_b)),
+        Instruction::ld_ind_n16_a => {
+            let address = operands.n16();
+            format!("ld [{}], a", operands.address(address))
+        },
+        Instruction::ld_a_ind_n16 => {
+            let address = operands.n16();
+            format!("ld a, [{}]", operands.address(address))
+        },
+        Instruction::ld_ind_r16mem_a { operand } => format!("ld {}, a", r16mem(operand)),
+        Instruction::ld_a_ind_r16mem { operand } => format!("ld a, {}", r16mem(operand)),
+        Instruction::ldh_ind_c_a => "ldh [c], a".to_owned(),
+        Instruction::ldh_a_ind_c => "ldh a, [c]".to_owned(),
+        Instruction::ldh_ind_n8_a => {
+            let address = 0xFF00 | operands.n8() as u16;
+            format!("ldh [{}], a", operands.address(address))
+        },
+        Instruction::ldh_a_ind_n8 => {
+            let address = 0xFF00 | operands.n8() as u16;
+            format!("ldh a, [{}]", operands.address(address))
+        },
+
+        Instruction::ld_r16_n16 { operand } => {
+            format!("ld {}, ${:04X}", r16(operand), operands.n16())
+        },
+        Instruction::ld_ind_n16_sp => {
+            let address = operands.n16();
+            format!("ld [{}], sp", operands.address(address))
+        },
+        Instruction::ld_sp_hl => "ld sp, hl".to_owned(),
+        Instruction::ld_hl_sp_n8 => format!("ld hl, sp{}", signed(operands.n8() as i8)),
+
+        Instruction::jr_i8 => {
+            let address = operands.relative_target();
+            target = Some(address);
+            format!("jr {}", operands.address(address))
+        },
+        Instruction::jr_cond_i8 { condition } => {
+            let address = operands.relative_target();
+            target = Some(address);
+            format!("jr {}, {}", cond(condition), operands.address(address))
+        },
+        Instruction::jp_n16 => {
+            let address = operands.n16();
+            target = Some(address);
+            format!("jp {}", operands.address(address))
+        },
+        Instruction::jp_cond_n16 { condition } => {
+            let address = operands.n16();
+            target = Some(address);
+            format!("jp {}, {}", cond(condition), operands.address(address))
+        },
+        Instruction::jp_hl => "jp hl".to_owned(),
+        Instruction::call_n16 => {
+            let address = operands.n16();
+            target = Some(address);
+            format!("call {}", operands.address(address))
+        },
+        Instruction::call_cond_n16 { condition } => {
+            let address = operands.n16();
+            target = Some(address);
+            format!("call {}, {}", cond(condition), operands.address(address))
+        },
+        Instruction::rst_tgt3 { target_address } => {
+            target = Some(target_address);
+            format!("rst ${:02X}", target_address)
+        },
+        Instruction::ret => "ret".to_owned(),
+        Instruction::reti => "reti".to_owned(),
+        Instruction::ret_cond { condition } => format!("ret {}", cond(condition)),
+
+        Instruction::pop_r16stk { operand } => format!("pop {}", r16stk(operand)),
+        Instruction::push_r16stk { operand } => format!("push {}", r16stk(operand)),
+
+        // the illegal opcodes lock up the CPU, RGBDS can only emit them as data
+        Instruction::unknown_opcode { opcode } => format!("db ${:02X}", opcode),
+        instruction => unreachable!("{:?} is not decoded from an opcode", instruction),
+    };
+
+    DisassembledInstruction {
+        address,
+        label: symbols.lookup(bus, address).map(str::to_owned),
+        bytes: operands.bytes,
+        text,
+        target,
+    }
+}
+
+// Linear sweep over `count` instructions starting at `address`
+pub fn disassemble_range<B: Bus>(
+    bus: &B,
+    address: u16,
+    count: usize,
+    symbols: &Symbols,
+) -> Vec<DisassembledInstruction> {
+    let mut address = address;
+
+    (0..count)
+        .map(|_| {
+            let instruction = disassemble(bus, address, symbols);
+            address = instruction.next_address();
+            instruction
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::emulator::Emulator;
+    use crate::tests::{setup_default_logger, test_emulator};
+
+    use super::*;
+
+    fn texts(emu: &Emulator, address: u16, count: usize, symbols: &Symbols) -> Vec<String> {
+        disassemble_range(&emu.system, address, count, symbols)
+            .into_iter()
+            .map(|instruction| instruction.text)
+            .collect()
+    }
+
+    #[test]
+    fn test_disassemble() {
+        let _guard = setup_default_logger();
+
+        let code: &[u8] = &[
+            0x00, // nop
+            0x3E, 0x42, // ld a, $42
+            0x21, 0x34, 0x12, // ld hl, $1234
+            0x22, // ld [hl+], a
+            0x70, // ld [hl], b
+            0xE0, 0x44, // ldh [$FF44], a
+            0xFA, 0x00, 0xC0, // ld a, [$C000]
+            0xCB, 0x7C, // bit 7, h
+            0xCB, 0x37, // swap a
+            0xE8, 0xFE, // add sp, -2
+            0xF8, 0x05, // ld hl, sp+5
+            0x10, 0x00, // stop
+            0xFE, 0x90, // cp a, $90
+            0xD3, // illegal
+            0xF5, // push af
+        ];
+        let emu = test_emulator(2, &[(0x0150, code)]);
+
+        assert_eq!(
+            texts(&emu, 0x0150, 16, &Symbols::new()),
+            [
+                "nop",
+                "ld a, $42",
+                "ld hl, $1234",
+                "ld [hl+], a",
+                "ld [hl], b",
+                "ldh [$FF44], a",
+                "ld a, [$C000]",
+                "bit 7, h",
+                "swap a",
+                "add sp, -2",
+                "ld hl, sp+5",
+                "stop",
+                "cp a, $90",
+                "db $D3",
+                "push af",
+                "nop",
+            ]
+        );
+
+        let instruction = disassemble(&emu.system, 0x0158, &Symbols::new());
+        assert_eq!(instruction.bytes, [0xE0, 0x44]);
+        assert_eq!(instruction.next_address(), 0x015A);
+        assert_eq!(instruction.to_string(), "0158: E0 44     ldh [$FF44], a");
+    }
+
+    #[test]
+    fn test_jump_targets() {
+        let _guard = setup_default_logger();
+
+        let code: &[u8] = &[
+            0x18, 0xFE, // jr $0150
+            0x20, 0x02, // jr nz, $0156
+            0xC3, 0x00, 0x40, // jp $4000
+            0xDC, 0x50, 0x01, // call c, $0150
+            0xFF, // rst $38
+            0xE9, // jp hl
+        ];
+        let emu = test_emulator(2, &[(0x0150, code)]);
+
+        let instructions = disassemble_range(&emu.system, 0x0150, 6, &Symbols::new());
+        let targets: Vec<_> = instructions
+            .iter()
+            .map(|instruction| instruction.target)
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                Some(0x0150),
+                Some(0x0156),
+                Some(0x4000),
+                Some(0x0150),
+                Some(0x0038),
+                None
+            ]
+        );
+        assert_eq!(instructions[1].text, "jr nz, $0156");
+        assert_eq!(instructions[4].text, "rst $38");
+    }
+
+    #[test]
+    fn test_symbols() {
+        let _guard = setup_default_logger();
+
+        let symbols = Symbols::parse(
+            "; File generated by rgblink\n\
+             [labels]\n\
+             00:0150 Main\n\
+             00:0152 Main.loop ; local label\n\
+             01:4000 BankOne\n\
+             02:4000 BankTwo\n\
+             00:C000 wCounter\n\
+             \n",
+        )
+        .unwrap();
+        assert_eq!(symbols.len(), 5);
+        assert_eq!(symbols.label(0x4000, Some(2)), Some("BankTwo"));
+        assert_eq!(symbols.label(0x4000, Some(3)), None);
+        assert_eq!(symbols.label(0x4000, None), Some("BankOne"));
+        assert_eq!(symbols.label(0xC000, Some(1)), Some("wCounter"));
+
+        let code: &[u8] = &[
+            0x00, // nop
+            0x00, // nop
+            0xEA, 0x00, 0xC0, // ld [wCounter], a
+            0xCD, 0x00, 0x40, // call BankTwo
+            0x18, 0xF8, // jr Main.loop
+        ];
+        // switch to bank 2 so the call resolves to the right label
+        let mut emu = test_emulator(4, &[(0x0150, code)]);
+        emu.system.write_byte(0x2000, 0x02);
+
+        let instructions = disassemble_range(&emu.system, 0x0150, 5, &symbols);
+        assert_eq!(instructions[0].label.as_deref(), Some("Main"));
+        assert_eq!(instructions[2].text, "ld [wCounter], a");
+        assert_eq!(instructions[3].text, "call BankTwo");
+        assert_eq!(instructions[4].text, "jr Main.loop");
+
+        assert!(matches!(
+            Symbols::parse("00:0150 Main\n0150\n"),
+            Err(SymbolError::InvalidLine { line: 2, .. })
+        ));
+    }
+}
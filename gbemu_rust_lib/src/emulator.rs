@@ -1,29 +1,19 @@
-use std::fmt::Debug;
+use std::error::Error;
+use std::fmt::{Debug, Display};
 use tracing::instrument;
-use tracing::trace;
 
+use crate::cartridge::CartridgeHeader;
 use crate::cpu::Cpu;
 use crate::cpu::instructions::Instruction;
-use crate::cpu::interrupts::Interrupt;
-use crate::cpu::interrupts::InterruptFlags;
+use crate::debugger::Debugger;
+use crate::graphics::screenshot::Screenshot;
 use crate::memory::mbc::new_mbc_from_buffer;
+use crate::model::BootRom;
+use crate::model::Model;
 use crate::serial::LogSerial;
 use crate::serial::Serial;
 use crate::system::System;
-
-macro_rules! trace_cpu_state {
-    ($self:ident) => {
-        trace!(
-            name: "cpu::state",
-            "{:?} PCMEM:{:02X},{:02X},{:02X},{:02X}",
-            $self.cpu,
-            $self.system.read_byte($self.cpu.registers.pc),
-            $self.system.read_byte($self.cpu.registers.pc.wrapping_add(1)),
-            $self.system.read_byte($self.cpu.registers.pc.wrapping_add(2)),
-            $self.system.read_byte($self.cpu.registers.pc.wrapping_add(3)),
-        )
-    };
-}
+use crate::trace::TraceRecorder;
 
 #[derive(Debug)]
 pub enum ExecutionError {
@@ -32,15 +22,82 @@ pub enum ExecutionError {
     MemoryRead { address: u16 },
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoadError {
+    UnsupportedMapper {
+        cartridge_type: u8,
+    },
+    TruncatedRom {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidHeaderField {
+        field: &'static str,
+        value: u8,
+    },
+    SizeMismatch {
+        name: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    UnsupportedMemoryLayout {
+        mapper: &'static str,
+        rom_banks: usize,
+        ram_banks: usize,
+    },
+}
+
+impl Display for LoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedMapper { cartridge_type } => {
+                write!(f, "Unsupported cartridge type 0x{:02X}", cartridge_type)
+            },
+            Self::TruncatedRom { expected, actual } => write!(
+                f,
+                "The ROM is truncated: expected at least {} bytes, got {}",
+                expected, actual
+            ),
+            Self::InvalidHeaderField { field, value } => write!(
+                f,
+                "Invalid value 0x{:02X} in cartridge header field '{}'",
+                value, field
+            ),
+            Self::SizeMismatch {
+                name,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "The {} has to be {} bytes big. Got: {}",
+                name, expected, actual
+            ),
+            Self::UnsupportedMemoryLayout {
+                mapper,
+                rom_banks,
+                ram_banks,
+            } => write!(
+                f,
+                "{} does not support {} ROM banks with {} RAM banks",
+                mapper, rom_banks, ram_banks
+            ),
+        }
+    }
+}
+
+impl Error for LoadError {}
+
 pub struct Emulator {
     pub cpu: Cpu,
     pub system: System,
+    pub debugger: Debugger,
+    pub trace: TraceRecorder,
 
-    graphics_enabled: bool,
+    header: CartridgeHeader,
 }
 
 impl Emulator {
-    pub fn new() -> Result<Self, String> {
+    pub fn new() -> Result<Self, LoadError> {
         Self::new_from_buffer(vec![0; 32 * 1024], true, None, None)
     }
 
@@ -49,23 +106,53 @@ impl Emulator {
         graphics_enabled: bool,
         cpu_option: Option<Cpu>,
         serial_option: Option<Box<dyn Serial>>,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, LoadError> {
+        Self::new_from_buffer_with_model(
+            rom,
+            Model::default(),
+            None,
+            graphics_enabled,
+            cpu_option,
+            serial_option,
+        )
+    }
+
+    pub fn new_from_buffer_with_model(
+        rom: Vec<u8>,
+        model: Model,
+        boot_rom_option: Option<Vec<u8>>,
+        graphics_enabled: bool,
+        cpu_option: Option<Cpu>,
+        serial_option: Option<Box<dyn Serial>>,
+    ) -> Result<Self, LoadError> {
         let serial = if let Some(s) = serial_option {
             s
         } else {
             Box::new(LogSerial::default())
         };
-        let mut mmu = System::new(new_mbc_from_buffer(rom)?, serial);
+        let boot_rom = if let Some(b) = boot_rom_option {
+            Some(BootRom::new_from_buffer(b, model)?)
+        } else {
+            None
+        };
+        let header = CartridgeHeader::try_from(rom.as_slice())?;
+        let mbc = new_mbc_from_buffer(rom, &header)?;
+        let mut mmu = System::new_with_model(mbc, serial, model, boot_rom);
+        mmu.graphics_enabled = graphics_enabled;
 
         let mut result = Self {
             cpu: if let Some(cpu) = cpu_option {
                 cpu
+            } else if mmu.boot_rom_mapped() {
+                Cpu::new_zeroed()
             } else {
-                Cpu::new(&mut mmu)
+                Cpu::new(&mut mmu, model)
             },
             system: mmu,
+            debugger: Debugger::default(),
+            trace: TraceRecorder::default(),
 
-            graphics_enabled,
+            header,
         };
 
         result.init();
@@ -73,13 +160,23 @@ impl Emulator {
         Ok(result)
     }
 
+    pub fn cartridge_header(&self) -> &CartridgeHeader {
+        &self.header
+    }
+
+    pub fn screenshot(&self) -> Screenshot {
+        Screenshot::from_framebuffer(&self.system.graphics.renderer.get_framebuffer())
+    }
+
+    pub fn model(&self) -> Model {
+        self.system.model()
+    }
+
     #[instrument(skip_all)]
     fn init(&mut self) {
-        trace_cpu_state!(self);
+        self.cpu.trace_state(&self.system);
 
-        self.cpu.current_instruction =
-            Instruction::decode_instruction(self.system.read_byte(self.cpu.registers.pc));
-        (self.cpu.registers.pc, _) = self.cpu.registers.pc.overflowing_add(1);
+        self.cpu.prefetch(&mut self.system);
     }
 
     #[instrument(skip_all, fields(
@@ -90,48 +187,10 @@ impl Emulator {
             self.system.oam_transfer_step();
         }
 
-        let mut cpu_completed = false;
-        if !self.cpu.halted {
-            cpu_completed = self.cpu.step(&mut self.system)?
-        }
-
-        let mut v_blank_interrupt = false;
-        if self.graphics_enabled {
-            v_blank_interrupt = self.system.graphics.step();
-            v_blank_interrupt |= self.system.graphics.step();
-        }
-
-        let timer_interrupt = self.system.io.timer.step()?;
-        let joypad_interrupt = self.system.io.joypad.interrupt();
-
-        if v_blank_interrupt {
-            self.cpu
-                .request_interrupt(&mut self.system, Interrupt::VBlank);
-        } else if timer_interrupt {
-            self.cpu
-                .request_interrupt(&mut self.system, Interrupt::Timer);
-        } else if joypad_interrupt {
-            self.cpu
-                .request_interrupt(&mut self.system, Interrupt::Joypad);
-        }
-
-        if self.system.io.interrupt_enable
-            & <InterruptFlags as Into<u8>>::into(self.system.io.interrupt_flags)
-            != 0
-        {
-            self.cpu.halted = false;
-        }
-
-        if !self.cpu.halted && cpu_completed {
-            match self.cpu.current_instruction {
-                Instruction::isr { .. } => {},
-                _ => {
-                    trace_cpu_state!(self);
-                },
-            }
-
-            self.cpu.generic_fetch(&mut self.system)?;
-        }
+        let completed = self.cpu.cycle(&mut self.system).inspect_err(|_| {
+            self.trace.dump(&self.debugger.symbols);
+        })?;
+        self.trace.tick(&self.cpu, &self.system, completed);
 
         Ok(())
     }
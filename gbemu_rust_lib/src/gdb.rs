@@ -0,0 +1,549 @@
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use tracing::{debug, info, warn};
+
+use crate::debugger::{Access, Breakpoint, RunTarget, StopReason, Watchpoint};
+use crate::emulator::Emulator;
+use crate::graphics::FRAME_CYCLES;
+
+// how long the emulator runs before checking for an interrupt request of the client
+const CONTINUE_CYCLES: u64 = FRAME_CYCLES as u64;
+// a single step only takes longer if the CPU is halted
+const STEP_CYCLES: u64 = 100 * FRAME_CYCLES as u64;
+
+const INTERRUPT: u8 = 0x03;
+
+// signals of the stop replies
+const SIGINT: u8 = 2;
+const SIGILL: u8 = 4;
+const SIGTRAP: u8 = 5;
+
+// AF, BC, DE, HL, SP and PC as 16-bit little endian values, the start of the z80 layout
+const REGISTER_COUNT: usize = 6;
+const REGISTER_PC: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resume {
+    Step,
+    Continue,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Response {
+    Reply(String),
+    Resume(Resume),
+    // the client detached or killed the session, the reply is sent before closing
+    Close(Option<String>),
+}
+
+fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(text.get(index..index + 2)?, 16).ok())
+        .collect()
+}
+
+// `addr,length` of the memory, breakpoint and watchpoint packets
+fn parse_range(text: &str) -> Option<(u16, u16)> {
+    let (address, length) = text.split_once(',')?;
+    Some((
+        u16::from_str_radix(address, 16).ok()?,
+        u16::from_str_radix(length, 16).ok()?,
+    ))
+}
+
+// Protocol state of one GDB session, independent of the transport
+#[derive(Debug, Default)]
+pub struct GdbStub {
+    // debugger ids by packet type and address
+    breakpoints: HashMap<(u8, u16), usize>,
+    // debugger ids by packet type, address and length
+    watchpoints: HashMap<(u8, u16, u16), usize>,
+}
+
+impl GdbStub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read_registers(emu: &Emulator) -> [u16; REGISTER_COUNT] {
+        let registers = &emu.cpu.registers;
+        [
+            registers.get_af(),
+            registers.get_bc(),
+            registers.get_de(),
+            registers.get_hl(),
+            registers.sp,
+            emu.cpu
+                .instruction_address()
+                .unwrap_or(emu.cpu.registers.pc),
+        ]
+    }
+
+    fn write_register(emu: &mut Emulator, index: usize, value: u16) {
+        let registers = &mut emu.cpu.registers;
+        match index {
+            0 => registers.set_af(value),
+            1 => registers.set_bc(value),
+            2 => registers.set_de(value),
+            3 => registers.set_hl(value),
+            4 => registers.sp = value,
+            REGISTER_PC => {
+                // the opcode at the new PC has to be fetched again
+                registers.pc = value;
+                emu.cpu.prefetch(&mut emu.system);
+            },
+            _ => {},
+        }
+    }
+
+    fn write_registers(emu: &mut Emulator, data: &str) -> Option<()> {
+        let bytes = parse_hex(data)?;
+        for (index, value) in bytes.chunks_exact(2).take(REGISTER_COUNT).enumerate() {
+            Self::write_register(emu, index, u16::from_le_bytes([value[0], value[1]]));
+        }
+
+        Some(())
+    }
+
+    fn read_memory(emu: &Emulator, address: u16, length: u16) -> String {
+        let bytes: Vec<u8> = (0..length)
+            .map(|offset| emu.system.read_byte(address.wrapping_add(offset)))
+            .collect();
+
+        hex(&bytes)
+    }
+
+    fn write_memory(emu: &mut Emulator, args: &str) -> Option<()> {
+        let (range, data) = args.split_once(':')?;
+        let (address, length) = parse_range(range)?;
+        let bytes = parse_hex(data)?;
+        if bytes.len() != length as usize {
+            return None;
+        }
+
+        for (offset, value) in bytes.into_iter().enumerate() {
+            emu.system
+                .write_byte(address.wrapping_add(offset as u16), value);
+        }
+
+        Some(())
+    }
+
+    // Z and z packets: `type,addr,kind`. Software and hardware breakpoints both map to debugger
+    // breakpoints, the ROM is never patched.
+    fn set_point(&mut self, emu: &mut Emulator, args: &str, insert: bool) -> Option<()> {
+        let (kind, range) = args.split_once(',')?;
+        let kind: u8 = kind.parse().ok()?;
+        let (address, length) = parse_range(range)?;
+        let debugger = &mut emu.debugger;
+
+        match kind {
+            0 | 1 => {
+                let key = (kind, address);
+                if insert {
+                    self.breakpoints
+                        .entry(key)
+                        .or_insert_with(|| debugger.add_breakpoint(Breakpoint::new(address)));
+                } else if let Some(id) = self.breakpoints.remove(&key) {
+                    debugger.remove_breakpoint(id);
+                }
+            },
+            2..=4 => {
+                let key = (kind, address, length);
+                if insert {
+                    // 2 watches writes, 3 reads and 4 both
+                    let end = address.saturating_add(length.max(1) - 1);
+                    let watchpoint = Watchpoint::new(address..=end, kind != 2, kind != 3, false);
+                    self.watchpoints
+                        .entry(key)
+                        .or_insert_with(|| debugger.add_watchpoint(watchpoint));
+                } else if let Some(id) = self.watchpoints.remove(&key) {
+                    debugger.remove_watchpoint(id);
+                }
+            },
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    pub fn handle_packet(&mut self, emu: &mut Emulator, packet: &str) -> Response {
+        debug!(name: "gdb::packet", "<- {}", packet);
+
+        let ok = |result: Option<()>| match result {
+            Some(()) => "OK".to_owned(),
+            None => "E01".to_owned(),
+        };
+
+        let args = packet.get(1..).unwrap_or_default();
+        let reply = match packet.as_bytes().first() {
+            Some(b'?') => format!("S{:02x}", SIGTRAP),
+            Some(b'g') => {
+                let bytes: Vec<u8> = Self::read_registers(emu)
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect();
+                hex(&bytes)
+            },
+            Some(b'G') => ok(Self::write_registers(emu, args)),
+            Some(b'p') => match usize::from_str_radix(args, 16) {
+                Ok(index) if index < REGISTER_COUNT => {
+                    hex(&Self::read_registers(emu)[index].to_le_bytes())
+                },
+                _ => "E01".to_owned(),
+            },
+            Some(b'P') => ok(args.split_once('=').and_then(|(index, value)| {
+                let index = usize::from_str_radix(index, 16).ok()?;
+                let value = parse_hex(value)?;
+                (index < REGISTER_COUNT && value.len() == 2).then(|| {
+                    Self::write_register(emu, index, u16::from_le_bytes([value[0], value[1]]))
+                })
+            })),
+            Some(b'm') => match parse_range(args) {
+                Some((address, length)) => Self::read_memory(emu, address, length),
+                None => "E01".to_owned(),
+            },
+            Some(b'M') => ok(Self::write_memory(emu, args)),
+            Some(b'Z') => ok(self.set_point(emu, args, true)),
+            Some(b'z') => ok(self.set_point(emu, args, false)),
+            Some(b's') => return Response::Resume(Resume::Step),
+            Some(b'c') => return Response::Resume(Resume::Continue),
+            Some(b'H') => "OK".to_owned(),
+            Some(b'D') => return Response::Close(Some("OK".to_owned())),
+            Some(b'k') => return Response::Close(None),
+            Some(b'q') => match args {
+                _ if args.starts_with("Supported") => "PacketSize=1000".to_owned(),
+                "Attached" => "1".to_owned(),
+                "fThreadInfo" => "m1".to_owned(),
+                "sThreadInfo" => "l".to_owned(),
+                "C" => "QC1".to_owned(),
+                _ => String::new(),
+            },
+            // everything else is unsupported, which is signaled with an empty reply
+            _ => String::new(),
+        };
+
+        Response::Reply(reply)
+    }
+
+    pub fn stop_reply(&self, emu: &Emulator, reason: &StopReason) -> String {
+        match reason {
+            StopReason::Watchpoint {
+                id,
+                address,
+                access,
+                ..
+            } => {
+                let kind = self
+                    .watchpoints
+                    .iter()
+                    .find(|(_, watchpoint)| *watchpoint == id)
+                    .map(|((kind, _, _), _)| *kind);
+                let name = match (kind, access) {
+                    (Some(4), _) => "awatch",
+                    (_, Access::Read) => "rwatch",
+                    _ => "watch",
+                };
+                format!("T{:02x}{}:{:x};", SIGTRAP, name, address)
+            },
+            _ => {
+                debug!(name: "gdb::stop", "{:?} at {:?}", reason, emu.cpu.instruction_address());
+                format!("S{:02x}", SIGTRAP)
+            },
+        }
+    }
+
+    // Runs the emulator and returns the stop reply. `interrupted` is polled regularly while
+    // continuing so the client can break into a running program.
+    pub fn resume<F>(&self, emu: &mut Emulator, resume: Resume, mut interrupted: F) -> String
+    where
+        F: FnMut() -> bool,
+    {
+        loop {
+            let result = match resume {
+                Resume::Step => emu.step_into(STEP_CYCLES),
+                Resume::Continue => emu.run_until(RunTarget::Breakpoint, CONTINUE_CYCLES),
+            };
+
+            match result {
+                Ok(StopReason::CycleLimit) if resume == Resume::Continue => {
+                    if interrupted() {
+                        return format!("S{:02x}", SIGINT);
+                    }
+                },
+                Ok(reason) => return self.stop_reply(emu, &reason),
+                Err(err) => {
+                    warn!("Execution stopped with an error: {:?}", err);
+                    return format!("S{:02x}", SIGILL);
+                },
+            }
+        }
+    }
+}
+
+// Packet framing of the remote serial protocol on top of a TCP connection
+struct Connection {
+    stream: TcpStream,
+    buffer: Vec<u8>,
+}
+
+impl Connection {
+    fn next_byte(&mut self) -> io::Result<u8> {
+        if let Some(byte) = self.buffer.pop() {
+            return Ok(byte);
+        }
+
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte)?;
+        Ok(byte[0])
+    }
+
+    // `$data#checksum`, acknowledgements and interrupts outside of a packet are skipped
+    fn read_packet(&mut self) -> io::Result<String> {
+        loop {
+            while self.next_byte()? != b'$' {}
+
+            let mut data = vec![];
+            loop {
+                match self.next_byte()? {
+                    b'#' => break,
+                    b'}' => data.push(self.next_byte()? ^ 0x20),
+                    byte => data.push(byte),
+                }
+            }
+
+            let checksum_text = [self.next_byte()?, self.next_byte()?];
+            let expected = std::str::from_utf8(&checksum_text)
+                .ok()
+                .and_then(|text| u8::from_str_radix(text, 16).ok());
+
+            if expected == Some(checksum(&data)) {
+                self.stream.write_all(b"+")?;
+                return Ok(String::from_utf8_lossy(&data).into_owned());
+            }
+
+            warn!("Dropping packet with invalid checksum");
+            self.stream.write_all(b"-")?;
+        }
+    }
+
+    fn write_packet(&mut self, data: &str) -> io::Result<()> {
+        debug!(name: "gdb::packet", "-> {}", data);
+
+        let packet = format!("${}#{:02x}", data, checksum(data.as_bytes()));
+        loop {
+            self.stream.write_all(packet.as_bytes())?;
+
+            // resend until the client acknowledged the packet
+            match self.next_byte()? {
+                b'+' => return Ok(()),
+                b'-' => continue,
+                byte => {
+                    self.buffer.push(byte);
+                    return Ok(());
+                },
+            }
+        }
+    }
+
+    fn interrupted(&mut self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut byte = [0];
+        let result = self.stream.read(&mut byte);
+        self.stream.set_nonblocking(false)?;
+
+        match result {
+            Ok(0) => Err(ErrorKind::ConnectionAborted.into()),
+            Ok(_) if byte[0] == INTERRUPT => Ok(true),
+            Ok(_) => {
+                self.buffer.push(byte[0]);
+                Ok(false)
+            },
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+pub struct GdbServer {
+    listener: TcpListener,
+}
+
+impl GdbServer {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(address)?,
+        })
+    }
+
+    pub fn local_port(&self) -> io::Result<u16> {
+        Ok(self.listener.local_addr()?.port())
+    }
+
+    // Serves a single client until it detaches, kills the session or disconnects
+    pub fn serve(&self, emu: &mut Emulator) -> io::Result<()> {
+        let (stream, peer) = self.listener.accept()?;
+        info!("GDB client connected from {}", peer);
+        stream.set_nodelay(true)?;
+
+        let mut connection = Connection {
+            stream,
+            buffer: vec![],
+        };
+        let mut stub = GdbStub::new();
+
+        let result = loop {
+            let packet = match connection.read_packet() {
+                Ok(packet) => packet,
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => break Ok(()),
+                Err(err) => break Err(err),
+            };
+
+            match stub.handle_packet(emu, &packet) {
+                Response::Reply(reply) => connection.write_packet(&reply)?,
+                Response::Resume(resume) => {
+                    let mut error = None;
+                    let reply = stub.resume(emu, resume, || {
+                        connection.interrupted().unwrap_or_else(|err| {
+                            error = Some(err);
+                            true
+                        })
+                    });
+
+                    if let Some(err) = error {
+                        break Err(err);
+                    }
+                    connection.write_packet(&reply)?;
+                },
+                Response::Close(reply) => {
+                    if let Some(reply) = reply {
+                        connection.write_packet(&reply)?;
+                    }
+                    break Ok(());
+                },
+            }
+        };
+
+        // the session is over, breakpoints of the client should not stay around
+        for id in stub.breakpoints.into_values() {
+            emu.debugger.remove_breakpoint(id);
+        }
+        for id in stub.watchpoints.into_values() {
+            emu.debugger.remove_watchpoint(id);
+        }
+        info!("GDB client disconnected");
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::{setup_default_logger, test_emulator};
+
+    use super::*;
+
+    fn reply(stub: &mut GdbStub, emu: &mut Emulator, packet: &str) -> String {
+        match stub.handle_packet(emu, packet) {
+            Response::Reply(reply) => reply,
+            response => panic!("Expected a reply to {}, got {:?}", packet, response),
+        }
+    }
+
+    #[test]
+    fn test_registers() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(2, &[]);
+        let mut stub = GdbStub::new();
+
+        emu.cpu.registers.set_bc(0x1234);
+        emu.cpu.registers.sp = 0xFFFE;
+        let registers = reply(&mut stub, &mut emu, "g");
+        assert_eq!(&registers[4..8], "3412");
+        assert_eq!(&registers[16..], "feff0001");
+        assert_eq!(reply(&mut stub, &mut emu, "p5"), "0001");
+
+        // jump to 0xC000 which contains INC A
+        emu.system.write_byte(0xC000, 0x3C);
+        assert_eq!(reply(&mut stub, &mut emu, "P5=00c0"), "OK");
+        assert_eq!(emu.cpu.instruction_address(), Some(0xC000));
+        assert_eq!(reply(&mut stub, &mut emu, "G0011"), "OK");
+        assert_eq!(emu.cpu.registers.a, 0x11);
+        assert_eq!(reply(&mut stub, &mut emu, "p9"), "E01");
+    }
+
+    #[test]
+    fn test_memory() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(2, &[(0x0100, &[0x3C, 0x18, 0xFD])]);
+        let mut stub = GdbStub::new();
+
+        assert_eq!(reply(&mut stub, &mut emu, "m100,3"), "3c18fd");
+        assert_eq!(reply(&mut stub, &mut emu, "Mc000,2:abcd"), "OK");
+        assert_eq!(emu.system.read_byte(0xC001), 0xCD);
+        assert_eq!(reply(&mut stub, &mut emu, "Mc000,2:ab"), "E01");
+        assert_eq!(reply(&mut stub, &mut emu, "mxyz"), "E01");
+        assert_eq!(reply(&mut stub, &mut emu, "vMustReplyEmpty"), "");
+    }
+
+    #[test]
+    fn test_breakpoints_and_watchpoints() {
+        let _guard = setup_default_logger();
+
+        let code = [
+            0x3C, // INC A
+            0xEA, 0x00, 0xC0, // LD (0xC000), A
+            0x18, 0xFA, // JR -6
+        ];
+        let mut emu = test_emulator(2, &[(0x0100, &code)]);
+        let mut stub = GdbStub::new();
+
+        assert_eq!(reply(&mut stub, &mut emu, "Z0,104,1"), "OK");
+        assert_eq!(
+            stub.resume(&mut emu, Resume::Continue, || false),
+            format!("S{:02x}", SIGTRAP)
+        );
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0104));
+
+        assert_eq!(reply(&mut stub, &mut emu, "z0,104,1"), "OK");
+        assert_eq!(emu.debugger.breakpoints().count(), 0);
+
+        assert_eq!(reply(&mut stub, &mut emu, "Z2,c000,1"), "OK");
+        assert_eq!(
+            stub.resume(&mut emu, Resume::Continue, || false),
+            format!("T{:02x}watch:c000;", SIGTRAP)
+        );
+        assert_eq!(reply(&mut stub, &mut emu, "z2,c000,1"), "OK");
+
+        assert_eq!(
+            stub.resume(&mut emu, Resume::Step, || false),
+            format!("S{:02x}", SIGTRAP)
+        );
+        assert_eq!(emu.cpu.instruction_address(), Some(0x0100));
+
+        // without breakpoints only the client can stop the emulator
+        let mut polls = 0;
+        let reply = stub.resume(&mut emu, Resume::Continue, || {
+            polls += 1;
+            polls == 3
+        });
+        assert_eq!(reply, format!("S{:02x}", SIGINT));
+    }
+}
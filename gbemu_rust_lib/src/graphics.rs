@@ -1,6 +1,7 @@
 pub mod object;
 pub mod registers;
 pub mod renderer;
+pub mod screenshot;
 pub mod tile;
 
 use std::array::from_fn;
@@ -11,6 +12,7 @@ use registers::GraphicsRegisters;
 use registers::PpuMode;
 use renderer::Renderer;
 use renderer::WGPURenderer;
+use screenshot::Screenshot;
 use tile::TileData;
 use tile::TileMap;
 use tracing::instrument;
@@ -29,10 +31,31 @@ const SCANLINE_CYCLES: usize = MODE_OAM_SCAN_CYCLES + MODE_DRAWING_CYCLES + MODE
 
 const NUM_LINES: usize = 153;
 
+// the system steps the PPU twice per M-cycle of the emulator
+pub(crate) const PPU_STEPS_PER_CYCLE: usize = 2;
+
+// emulator steps per frame
+pub const FRAME_CYCLES: usize = SCANLINE_CYCLES * (NUM_LINES + 1) / PPU_STEPS_PER_CYCLE;
+
+pub const NUM_OBJECTS: usize = OAM_SIZE / 4;
+const MAX_OBJECTS_PER_LINE: usize = 10;
+
+// An OAM entry as seen by the OAM scan of the current line
+pub struct ObjectInfo {
+    pub object: Object,
+    pub preview: Screenshot,
+    pub on_line: bool,
+    // on the line, but beyond the limit of objects per line
+    pub dropped: bool,
+}
+
 pub struct Ppu {
     pub registers: GraphicsRegisters,
     pub tile_data: TileData,
     pub tile_maps: [TileMap; 2],
+    // VRAM bank 1 of the CGB, a second set of tiles and the attributes of both tile maps
+    pub tile_data_bank_1: TileData,
+    pub attribute_maps: [TileMap; 2],
 
     oam: [u8; OAM_SIZE],
     object_buffer: Vec<Object>,
@@ -48,9 +71,11 @@ impl Default for Ppu {
             registers: GraphicsRegisters::new(),
             tile_data: TileData::default(),
             tile_maps: from_fn(|_| TileMap::default()),
+            tile_data_bank_1: TileData::default(),
+            attribute_maps: from_fn(|_| TileMap::default()),
 
             oam: [0; OAM_SIZE],
-            object_buffer: Vec::with_capacity(10),
+            object_buffer: Vec::with_capacity(MAX_OBJECTS_PER_LINE),
 
             renderer: Box::new(WGPURenderer::default()),
 
@@ -60,6 +85,35 @@ impl Default for Ppu {
 }
 
 impl Ppu {
+    pub fn tile_data(&self, bank: usize) -> &TileData {
+        match bank {
+            0 => &self.tile_data,
+            _ => &self.tile_data_bank_1,
+        }
+    }
+
+    pub fn tile_data_mut(&mut self, bank: usize) -> &mut TileData {
+        match bank {
+            0 => &mut self.tile_data,
+            _ => &mut self.tile_data_bank_1,
+        }
+    }
+
+    // the tile maps of bank 0 or the attribute maps of bank 1
+    pub fn tile_maps(&self, bank: usize) -> &[TileMap; 2] {
+        match bank {
+            0 => &self.tile_maps,
+            _ => &self.attribute_maps,
+        }
+    }
+
+    pub fn tile_maps_mut(&mut self, bank: usize) -> &mut [TileMap; 2] {
+        match bank {
+            0 => &mut self.tile_maps,
+            _ => &mut self.attribute_maps,
+        }
+    }
+
     pub fn read_oam_byte(&self, address: u16) -> u8 {
         assert!((address as usize) < OAM_SIZE);
 
@@ -72,6 +126,43 @@ impl Ppu {
         self.oam[address as usize] = value;
     }
 
+    pub fn object(&self, index: usize) -> Object {
+        self.oam[index * 4..(index + 1) * 4].into()
+    }
+
+    pub fn object_height(&self) -> u8 {
+        if self.registers.lcd_control.sprite_double_size {
+            16
+        } else {
+            8
+        }
+    }
+
+    pub fn objects(&self) -> Vec<ObjectInfo> {
+        let line = self.registers.get_lcd_ly();
+        let mut found = 0;
+
+        (0..NUM_OBJECTS)
+            .map(|index| {
+                let object = self.object(index);
+                let palette = self
+                    .registers
+                    .get_obj_palette(object.sprite_flags.palette as usize);
+                let on_line = object.is_on_line(line, self.object_height());
+                if on_line {
+                    found += 1;
+                }
+
+                ObjectInfo {
+                    object,
+                    preview: object.to_image(&self.tile_data, self.object_height(), palette),
+                    on_line,
+                    dropped: on_line && found > MAX_OBJECTS_PER_LINE,
+                }
+            })
+            .collect()
+    }
+
     #[instrument(skip_all, fields(ly = self.registers.get_lcd_ly()))]
     pub fn render_background(&mut self) {
         let tile_map = &self.tile_maps[self.registers.lcd_control.background_tile_map as usize];
@@ -114,11 +205,12 @@ impl Ppu {
     pub fn render_window(&mut self) {}
 
     pub fn render_objects(&mut self) {
+        let height = self.object_height();
         for obj in &self.object_buffer {
-            let tile = self.tile_data.get_tile(true, obj.tile_number);
-            // TODO: double height mode
-            let tile_row = self.registers.get_lcd_ly() - (obj.pos_y - 16);
-            let pixels = tile.get_row(tile_row as usize);
+            // the OAM scan only selects objects on the current line
+            let row = self.registers.get_lcd_ly() as i16 - obj.screen_y();
+            let (tile_number, tile_row) = obj.tile_row(row as usize, height);
+            let pixels = self.tile_data.get_tile(true, tile_number).get_row(tile_row);
 
             let low = min(0, obj.pos_x as isize - 8).unsigned_abs();
             let high = min(8, LCD_WIDTH - obj.pos_x as usize);
@@ -192,18 +284,11 @@ impl Ppu {
                         self.registers.get_lcd_ly()
                     );
 
-                    for i in 0..OAM_SIZE / 4 {
-                        let obj: Object = self.oam[i * 4..(i + 1) * 4].into();
-
-                        let obj_height = if self.registers.lcd_control.sprite_double_size {
-                            16
-                        } else {
-                            8
-                        };
+                    for i in 0..NUM_OBJECTS {
+                        let obj = self.object(i);
 
-                        if self.object_buffer.len() < 10
-                            && (self.registers.get_lcd_ly() >= obj.pos_y.overflowing_sub(16).0)
-                            && (self.registers.get_lcd_ly() < obj.pos_y + obj_height - 16)
+                        if self.object_buffer.len() < MAX_OBJECTS_PER_LINE
+                            && obj.is_on_line(self.registers.get_lcd_ly(), self.object_height())
                         {
                             trace!("Found object {:?}", obj);
                             self.object_buffer.push(obj);
@@ -224,7 +309,7 @@ impl Ppu {
             },
         }
 
-        return interrupt;
+        interrupt
     }
 
     #[cfg(feature = "nogfx")]
@@ -233,6 +318,7 @@ impl Ppu {
 
 #[cfg(test)]
 mod tests {
+    use super::tile::{IDENTITY_PALETTE, Pixel};
     use super::*;
 
     #[test]
@@ -277,4 +363,73 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_objects() {
+        let mut ppu = Ppu::default();
+        ppu.registers.set_obj_palette(0, IDENTITY_PALETTE);
+        // tile 2 has a single dark pixel in its top left corner
+        ppu.tile_data.set_byte(2 * 16, 0x80);
+        ppu.tile_data.set_byte(2 * 16 + 1, 0x80);
+
+        // eleven objects on line 0, the last one is dropped by the OAM scan
+        for index in 0..11 {
+            let address = index as u16 * 4;
+            ppu.write_oam_byte(address, 16);
+            ppu.write_oam_byte(address + 1, 8 + index * 8);
+            ppu.write_oam_byte(address + 2, 2);
+        }
+        // partially above the screen, flipped in both directions
+        ppu.write_oam_byte(11 * 4, 10);
+        ppu.write_oam_byte(11 * 4 + 2, 2);
+        ppu.write_oam_byte(11 * 4 + 3, 0b0110_0000);
+
+        let objects = ppu.objects();
+        assert_eq!(objects.len(), NUM_OBJECTS);
+        assert!(
+            objects[..10]
+                .iter()
+                .all(|info| info.on_line && !info.dropped)
+        );
+        assert!(objects[10].on_line && objects[10].dropped);
+        assert!(objects[11].on_line && objects[11].dropped);
+        assert!(objects[12..].iter().all(|info| !info.on_line));
+
+        assert_eq!(objects[0].preview.get_pixel(0, 0), Pixel::Color3);
+        assert_eq!(objects[11].preview.get_pixel(7, 7), Pixel::Color3);
+        assert_eq!(objects[11].preview.get_pixel(0, 0), Pixel::Color0);
+
+        ppu.registers.set_lcd_control(0b0000_0100);
+        let objects = ppu.objects();
+        assert_eq!(objects[0].preview.height, 16);
+        // the bottom half uses tile 3
+        assert_eq!(objects[0].preview.get_pixel(0, 8), Pixel::Color0);
+    }
+
+    #[test]
+    fn test_render_double_height_objects() {
+        let mut ppu = Ppu::default();
+        ppu.registers.set_lcd_control(0b0000_0100);
+        // tile 2 has a dark pixel in its top left corner, tile 3 in its bottom left corner
+        ppu.tile_data.set_byte(2 * 16, 0x80);
+        ppu.tile_data.set_byte(2 * 16 + 1, 0x80);
+        ppu.tile_data.set_byte(3 * 16 + 14, 0x80);
+        ppu.tile_data.set_byte(3 * 16 + 15, 0x80);
+
+        // the lowest bit of the tile number is ignored, the second object is flipped vertically
+        let objects: [[u8; 4]; 2] = [[16, 8, 3, 0], [16, 16, 2, 0b0100_0000]];
+        for line in [0, 8, 15] {
+            ppu.registers.set_lcd_ly(line);
+            ppu.object_buffer = objects.iter().map(|object| object[..].into()).collect();
+            ppu.render_objects();
+        }
+
+        let framebuffer = ppu.renderer.get_framebuffer();
+        for line in [0, 15] {
+            assert_eq!(framebuffer[line][0], Pixel::Color3);
+            assert_eq!(framebuffer[line][8], Pixel::Color3);
+        }
+        assert_eq!(framebuffer[8][0], Pixel::Color0);
+        assert_eq!(framebuffer[8][8], Pixel::Color0);
+    }
 }
@@ -1,5 +1,11 @@
+use super::screenshot::Screenshot;
+use super::tile::{Pixel, TileData};
 use crate::utils::bit_operations::bit;
 
+// objects are placed with an offset so they can be partially off screen
+const OBJECT_X_OFFSET: i16 = 8;
+const OBJECT_Y_OFFSET: i16 = 16;
+
 #[derive(Default, Debug, Clone, Copy)]
 pub struct SpriteFlags {
     pub background_priority: bool,
@@ -30,7 +36,7 @@ impl From<SpriteFlags> for u8 {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub struct Object {
     pub pos_x: u8,
     pub pos_y: u8,
@@ -50,3 +56,60 @@ impl From<&[u8]> for Object {
         }
     }
 }
+
+impl Object {
+    pub fn screen_x(&self) -> i16 {
+        self.pos_x as i16 - OBJECT_X_OFFSET
+    }
+
+    pub fn screen_y(&self) -> i16 {
+        self.pos_y as i16 - OBJECT_Y_OFFSET
+    }
+
+    pub fn is_on_line(&self, line: u8, height: u8) -> bool {
+        (self.screen_y()..self.screen_y() + height as i16).contains(&(line as i16))
+    }
+
+    // Tile number and row of that tile which are shown on the given row of the object
+    pub fn tile_row(&self, row: usize, height: u8) -> (u8, usize) {
+        let row = if self.sprite_flags.flip_y {
+            height as usize - 1 - row
+        } else {
+            row
+        };
+
+        // in 8x16 mode the lowest bit of the tile number is ignored
+        let tile_number = if height == 16 {
+            (self.tile_number & 0xFE) | (row / 8) as u8
+        } else {
+            self.tile_number
+        };
+
+        (tile_number, row % 8)
+    }
+
+    // The object as it appears on screen, flipped and shaded through the palette
+    pub fn to_image(&self, tile_data: &TileData, height: u8, palette: u8) -> Screenshot {
+        let mut pixels = vec![Pixel::default(); 8 * height as usize];
+
+        for y in 0..height as usize {
+            let (tile_number, row) = self.tile_row(y, height);
+            let tile = tile_data.get_tile(true, tile_number);
+
+            for (column, pixel) in tile.get_row(row).into_iter().enumerate() {
+                let x = if self.sprite_flags.flip_x {
+                    7 - column
+                } else {
+                    column
+                };
+                pixels[y * 8 + x] = pixel.with_palette(palette);
+            }
+        }
+
+        Screenshot {
+            width: 8,
+            height: height as usize,
+            pixels,
+        }
+    }
+}
@@ -0,0 +1,145 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use super::tile::Pixel;
+use super::{LCD_HEIGHT, LCD_WIDTH};
+
+// RGB colors for the four shades, from lightest to darkest
+pub type Palette = [[u8; 3]; 4];
+
+pub const GRAYSCALE_PALETTE: Palette = [
+    [0xFF, 0xFF, 0xFF],
+    [0xAA, 0xAA, 0xAA],
+    [0x55, 0x55, 0x55],
+    [0x00, 0x00, 0x00],
+];
+
+pub(crate) fn encode_png<W: Write>(
+    writer: W,
+    width: usize,
+    height: usize,
+    pixels: &[Pixel],
+    palette: &Palette,
+    scale: usize,
+) -> std::io::Result<()> {
+    let scale = scale.max(1);
+
+    let mut encoder = png::Encoder::new(writer, (width * scale) as u32, (height * scale) as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let mut data = Vec::with_capacity(pixels.len() * scale * scale * 3);
+    for row in pixels.chunks(width) {
+        let scaled_row: Vec<u8> = row
+            .iter()
+            .flat_map(|pixel| {
+                std::iter::repeat_n(palette[<u8>::from(*pixel) as usize], scale).flatten()
+            })
+            .collect();
+
+        for _ in 0..scale {
+            data.extend_from_slice(&scaled_row);
+        }
+    }
+
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screenshot {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Pixel>,
+}
+
+impl Screenshot {
+    pub fn from_framebuffer(framebuffer: &[[Pixel; LCD_WIDTH]; LCD_HEIGHT]) -> Self {
+        Self {
+            width: LCD_WIDTH,
+            height: LCD_HEIGHT,
+            pixels: framebuffer.iter().flatten().copied().collect(),
+        }
+    }
+
+    pub fn get_pixel(&self, x: usize, y: usize) -> Pixel {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn encode_png<W: Write>(
+        &self,
+        writer: W,
+        palette: &Palette,
+        scale: usize,
+    ) -> std::io::Result<()> {
+        encode_png(
+            writer,
+            self.width,
+            self.height,
+            &self.pixels,
+            palette,
+            scale,
+        )
+    }
+
+    pub fn to_png(&self, palette: &Palette, scale: usize) -> std::io::Result<Vec<u8>> {
+        let mut data = vec![];
+        self.encode_png(&mut data, palette, scale)?;
+
+        Ok(data)
+    }
+
+    pub fn write_png<P: AsRef<Path>>(
+        &self,
+        path: P,
+        palette: &Palette,
+        scale: usize,
+    ) -> std::io::Result<()> {
+        self.encode_png(BufWriter::new(File::create(path)?), palette, scale)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode(data: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let mut reader = png::Decoder::new(std::io::Cursor::new(data))
+            .read_info()
+            .unwrap();
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap()];
+        let info = reader.next_frame(&mut buffer).unwrap();
+        buffer.truncate(info.buffer_size());
+
+        (info, buffer)
+    }
+
+    #[test]
+    fn test_encode_scaled() {
+        let mut framebuffer = [[Pixel::Color0; LCD_WIDTH]; LCD_HEIGHT];
+        framebuffer[0][1] = Pixel::Color3;
+        framebuffer[1][0] = Pixel::Color2;
+
+        let screenshot = Screenshot::from_framebuffer(&framebuffer);
+        assert_eq!(screenshot.get_pixel(1, 0), Pixel::Color3);
+
+        let (info, data) = decode(&screenshot.to_png(&GRAYSCALE_PALETTE, 2).unwrap());
+        assert_eq!((info.width, info.height), (320, 288));
+        assert_eq!(info.color_type, png::ColorType::Rgb);
+
+        let pixel = |x: usize, y: usize| {
+            let index = (y * 320 + x) * 3;
+            [data[index], data[index + 1], data[index + 2]]
+        };
+        assert_eq!(pixel(0, 0), [0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(1, 1), [0xFF, 0xFF, 0xFF]);
+        assert_eq!(pixel(2, 0), [0x00, 0x00, 0x00]);
+        assert_eq!(pixel(3, 1), [0x00, 0x00, 0x00]);
+        assert_eq!(pixel(0, 2), [0x55, 0x55, 0x55]);
+        assert_eq!(pixel(1, 3), [0x55, 0x55, 0x55]);
+    }
+}
@@ -1,8 +1,19 @@
 use std::array::from_fn;
 
+use super::screenshot::Screenshot;
+
 pub(super) const TILE_SIZE: usize = 16;
 pub(super) const NUM_TILES: usize = 0x1800 / TILE_SIZE;
 
+// tiles per row and column of a tile map
+pub const TILE_MAP_WIDTH: usize = 32;
+
+// tiles per row of the tile data image
+const TILE_SHEET_COLUMNS: usize = 16;
+
+// palette register value which maps every color to the shade with the same number
+pub const IDENTITY_PALETTE: u8 = 0b11_10_01_00;
+
 #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Pixel {
     #[default]
@@ -35,6 +46,13 @@ impl From<Pixel> for u8 {
     }
 }
 
+impl Pixel {
+    // maps the color number to a shade like BGP, OBP0 and OBP1 do
+    pub fn with_palette(self, palette: u8) -> Pixel {
+        ((palette >> (u8::from(self) * 2)) & 0b11).into()
+    }
+}
+
 #[derive(Default, Debug)]
 pub struct TileRow {
     pub bytes: [u8; 2],
@@ -80,7 +98,7 @@ impl Tile {
 
 #[derive(Default)]
 pub struct TileMap {
-    pub tiles: [[u8; 32]; 32],
+    pub tiles: [[u8; TILE_MAP_WIDTH]; TILE_MAP_WIDTH],
 }
 
 impl TileMap {
@@ -93,6 +111,37 @@ impl TileMap {
         assert!(address < 32 * 32);
         self.tiles[address as usize / 32][address as usize % 32] = value;
     }
+
+    // The whole 256x256 background the map describes, shaded through the palette
+    pub fn to_image(
+        &self,
+        tile_data: &TileData,
+        tile_data_select: bool,
+        palette: u8,
+    ) -> Screenshot {
+        let width = TILE_MAP_WIDTH * 8;
+        let height = TILE_MAP_WIDTH * 8;
+        let mut pixels = vec![Pixel::default(); width * height];
+
+        for (y, tiles) in self.tiles.iter().enumerate() {
+            for (x, tile_number) in tiles.iter().enumerate() {
+                let tile = tile_data.get_tile(tile_data_select, *tile_number);
+
+                for row in 0..8 {
+                    for (column, pixel) in tile.get_row(row).into_iter().enumerate() {
+                        pixels[(y * 8 + row) * width + x * 8 + column] =
+                            pixel.with_palette(palette);
+                    }
+                }
+            }
+        }
+
+        Screenshot {
+            width,
+            height,
+            pixels,
+        }
+    }
 }
 
 pub struct TileData {
@@ -118,6 +167,40 @@ impl TileData {
         }
     }
 
+    // Tile number which selects the tile at `index` in the given addressing mode, the inverse
+    // of `get_tile`
+    pub fn tile_number(index: usize, tile_data_select: bool) -> Option<u8> {
+        match (tile_data_select, index) {
+            (true, 0..256) => Some(index as u8),
+            (false, 128..NUM_TILES) => Some(((index as i16 - 256) as i8) as u8),
+            _ => None,
+        }
+    }
+
+    // All tiles in rows of 16, shaded through the palette
+    pub fn to_image(&self, palette: u8) -> Screenshot {
+        let width = TILE_SHEET_COLUMNS * 8;
+        let height = NUM_TILES / TILE_SHEET_COLUMNS * 8;
+        let mut pixels = vec![Pixel::default(); width * height];
+
+        for (index, tile) in self.tiles.iter().enumerate() {
+            let x = (index % TILE_SHEET_COLUMNS) * 8;
+            let y = (index / TILE_SHEET_COLUMNS) * 8;
+
+            for row in 0..8 {
+                for (column, pixel) in tile.get_row(row).into_iter().enumerate() {
+                    pixels[(y + row) * width + x + column] = pixel.with_palette(palette);
+                }
+            }
+        }
+
+        Screenshot {
+            width,
+            height,
+            pixels,
+        }
+    }
+
     pub fn get_byte(&self, address: u16) -> u8 {
         self.tiles[address as usize / 16].get_byte(address % 16)
     }
@@ -150,6 +233,82 @@ mod tests {
         assert_eq!(row.get_pixel(7), Pixel::Color3);
     }
 
+    #[test]
+    fn test_with_palette() {
+        assert_eq!(Pixel::Color2.with_palette(IDENTITY_PALETTE), Pixel::Color2);
+        // the default BGP after boot
+        assert_eq!(Pixel::Color0.with_palette(0xFC), Pixel::Color0);
+        assert_eq!(Pixel::Color1.with_palette(0xFC), Pixel::Color3);
+        assert_eq!(Pixel::Color3.with_palette(0b00_01_10_11), Pixel::Color0);
+    }
+
+    #[test]
+    fn test_tile_number() {
+        let tiles = TileData::default();
+
+        for index in 0..NUM_TILES {
+            for tile_data_select in [true, false] {
+                if let Some(tile_number) = TileData::tile_number(index, tile_data_select) {
+                    assert_eq!(
+                        &tiles.tiles[index] as *const _,
+                        tiles.get_tile(tile_data_select, tile_number) as *const _
+                    );
+                }
+            }
+        }
+
+        assert_eq!(TileData::tile_number(0, false), None);
+        assert_eq!(TileData::tile_number(300, true), None);
+        assert_eq!(TileData::tile_number(128, false), Some(0x80));
+        assert_eq!(TileData::tile_number(256, false), Some(0x00));
+    }
+
+    #[test]
+    fn test_to_image() {
+        let mut tiles = TileData::default();
+        // first row of tile 17 is color 3 on the left half
+        tiles.set_byte(17 * 16, 0xF0);
+        tiles.set_byte(17 * 16 + 1, 0xF0);
+
+        let image = tiles.to_image(IDENTITY_PALETTE);
+        assert_eq!((image.width, image.height), (128, 192));
+        assert_eq!(image.get_pixel(8, 8), Pixel::Color3);
+        assert_eq!(image.get_pixel(11, 8), Pixel::Color3);
+        assert_eq!(image.get_pixel(12, 8), Pixel::Color0);
+        assert_eq!(image.get_pixel(8, 9), Pixel::Color0);
+
+        let image = tiles.to_image(0b00_01_10_11);
+        assert_eq!(image.get_pixel(8, 8), Pixel::Color0);
+        assert_eq!(image.get_pixel(0, 0), Pixel::Color3);
+    }
+
+    #[test]
+    fn test_tile_map_to_image() {
+        let mut tiles = TileData::default();
+        // tile 1 is color 3 on its first row, tile 0x81 color 1 everywhere
+        tiles.set_byte(16, 0xFF);
+        tiles.set_byte(17, 0xFF);
+        for row in 0..8 {
+            tiles.set_byte(0x81 * 16 + row * 2, 0xFF);
+        }
+
+        let mut map = TileMap::default();
+        map.set_byte(33, 0x01);
+        map.set_byte(32 * 32 - 1, 0x81);
+
+        let image = map.to_image(&tiles, true, IDENTITY_PALETTE);
+        assert_eq!((image.width, image.height), (256, 256));
+        assert_eq!(image.get_pixel(8, 8), Pixel::Color3);
+        assert_eq!(image.get_pixel(15, 8), Pixel::Color3);
+        assert_eq!(image.get_pixel(8, 9), Pixel::Color0);
+        assert_eq!(image.get_pixel(255, 255), Pixel::Color1);
+
+        // in 8800 mode 0x01 refers to tile 257, while tiles from 0x80 are shared
+        let image = map.to_image(&tiles, false, IDENTITY_PALETTE);
+        assert_eq!(image.get_pixel(8, 8), Pixel::Color0);
+        assert_eq!(image.get_pixel(255, 255), Pixel::Color1);
+    }
+
     #[test]
     fn test_get_tile() {
         let tiles = TileData::default();
@@ -0,0 +1,306 @@
+use std::fmt::Display;
+
+use crate::cpu::interrupts::InterruptFlags;
+use crate::graphics::registers::{LcdControlFlags, LcdStatusFlags};
+use crate::serial::SerialControl;
+use crate::timer::TimerFrequency;
+use crate::utils::bit_operations::bit;
+
+pub const IO_REGISTERS: [(u16, &str); 71] = [
+    (0xFF00, "JOYP"),
+    (0xFF01, "SB"),
+    (0xFF02, "SC"),
+    (0xFF04, "DIV"),
+    (0xFF05, "TIMA"),
+    (0xFF06, "TMA"),
+    (0xFF07, "TAC"),
+    (0xFF0F, "IF"),
+    (0xFF10, "NR10"),
+    (0xFF11, "NR11"),
+    (0xFF12, "NR12"),
+    (0xFF13, "NR13"),
+    (0xFF14, "NR14"),
+    (0xFF16, "NR21"),
+    (0xFF17, "NR22"),
+    (0xFF18, "NR23"),
+    (0xFF19, "NR24"),
+    (0xFF1A, "NR30"),
+    (0xFF1B, "NR31"),
+    (0xFF1C, "NR32"),
+    (0xFF1D, "NR33"),
+    (0xFF1E, "NR34"),
+    (0xFF20, "NR41"),
+    (0xFF21, "NR42"),
+    (0xFF22, "NR43"),
+    (0xFF23, "NR44"),
+    (0xFF24, "NR50"),
+    (0xFF25, "NR51"),
+    (0xFF26, "NR52"),
+    (0xFF30, "WAVE0"),
+    (0xFF31, "WAVE1"),
+    (0xFF32, "WAVE2"),
+    (0xFF33, "WAVE3"),
+    (0xFF34, "WAVE4"),
+    (0xFF35, "WAVE5"),
+    (0xFF36, "WAVE6"),
+    (0xFF37, "WAVE7"),
+    (0xFF38, "WAVE8"),
+    (0xFF39, "WAVE9"),
+    (0xFF3A, "WAVEA"),
+    (0xFF3B, "WAVEB"),
+    (0xFF3C, "WAVEC"),
+    (0xFF3D, "WAVED"),
+    (0xFF3E, "WAVEE"),
+    (0xFF3F, "WAVEF"),
+    (0xFF40, "LCDC"),
+    (0xFF41, "STAT"),
+    (0xFF42, "SCY"),
+    (0xFF43, "SCX"),
+    (0xFF44, "LY"),
+    (0xFF45, "LYC"),
+    (0xFF46, "DMA"),
+    (0xFF47, "BGP"),
+    (0xFF48, "OBP0"),
+    (0xFF49, "OBP1"),
+    (0xFF4A, "WY"),
+    (0xFF4B, "WX"),
+    (0xFF4D, "KEY1"),
+    (0xFF4F, "VBK"),
+    (0xFF50, "BANK"),
+    (0xFF51, "HDMA1"),
+    (0xFF52, "HDMA2"),
+    (0xFF53, "HDMA3"),
+    (0xFF54, "HDMA4"),
+    (0xFF55, "HDMA5"),
+    (0xFF68, "BCPS"),
+    (0xFF69, "BCPD"),
+    (0xFF6A, "OCPS"),
+    (0xFF6B, "OCPD"),
+    (0xFF70, "SVBK"),
+    (0xFFFF, "IE"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterField {
+    pub name: &'static str,
+    pub value: String,
+}
+
+impl RegisterField {
+    fn new(name: &'static str, value: impl Display) -> Self {
+        Self {
+            name,
+            value: value.to_string(),
+        }
+    }
+
+    fn flag(name: &'static str, value: bool) -> Self {
+        Self::new(name, if value { "on" } else { "off" })
+    }
+}
+
+impl Display for RegisterField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.name, self.value)
+    }
+}
+
+pub fn io_register_name(address: u16) -> Option<&'static str> {
+    IO_REGISTERS
+        .iter()
+        .find(|(register, _)| *register == address)
+        .map(|(_, name)| *name)
+}
+
+fn decode_joypad(value: u8) -> Vec<RegisterField> {
+    // all bits are active low
+    let buttons = !bit!(value: u8, 5);
+    let directions = !bit!(value: u8, 4);
+    let keys = [
+        ("Right", "A"),
+        ("Left", "B"),
+        ("Up", "Select"),
+        ("Down", "Start"),
+    ];
+
+    let pressed: Vec<_> = keys
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| value & (1 << index) == 0)
+        .flat_map(|(_, (direction, button))| {
+            [(directions, *direction), (buttons, *button)]
+                .into_iter()
+                .filter_map(|(selected, key)| selected.then_some(key))
+        })
+        .collect();
+
+    vec![
+        RegisterField::flag("Buttons", buttons),
+        RegisterField::flag("Directions", directions),
+        RegisterField::new("Pressed", pressed.join(" ")),
+    ]
+}
+
+fn decode_interrupts(value: u8) -> Vec<RegisterField> {
+    let flags: InterruptFlags = value.into();
+    vec![
+        RegisterField::flag("VBlank", flags.v_blank),
+        RegisterField::flag("LCD", flags.lcd),
+        RegisterField::flag("Timer", flags.timer),
+        RegisterField::flag("Serial", flags.serial),
+        RegisterField::flag("Joypad", flags.joypad),
+    ]
+}
+
+fn decode_palette(value: u8) -> Vec<RegisterField> {
+    const COLORS: [&str; 4] = ["Color 0", "Color 1", "Color 2", "Color 3"];
+
+    COLORS
+        .iter()
+        .enumerate()
+        .map(|(index, name)| RegisterField::new(name, (value >> (index * 2)) & 0b11))
+        .collect()
+}
+
+// Splits the value of the register into its fields, empty for plain values and unknown registers
+pub fn decode_io_register(address: u16, value: u8) -> Vec<RegisterField> {
+    match address {
+        0xFF00 => decode_joypad(value),
+        0xFF02 => {
+            let control: SerialControl = value.into();
+            vec![
+                RegisterField::flag("Transfer", control.enabled),
+                RegisterField::new(
+                    "Clock",
+                    if control.clock_select {
+                        "internal"
+                    } else {
+                        "external"
+                    },
+                ),
+            ]
+        },
+        0xFF07 => {
+            let frequency = match TimerFrequency::from(value) {
+                TimerFrequency::Cycles4 => "262144 Hz",
+                TimerFrequency::Cycles16 => "65536 Hz",
+                TimerFrequency::Cycles64 => "16384 Hz",
+                TimerFrequency::Cycles256 => "4096 Hz",
+            };
+            vec![
+                RegisterField::flag("Enabled", bit!(value: u8, 2)),
+                RegisterField::new("Frequency", frequency),
+            ]
+        },
+        0xFF0F | 0xFFFF => decode_interrupts(value),
+        0xFF40 => {
+            let control: LcdControlFlags = value.into();
+            let map = |high| if high { "9C00" } else { "9800" };
+            vec![
+                RegisterField::flag("LCD", control.enabled),
+                RegisterField::new("Window map", map(control.window_tile_map)),
+                RegisterField::flag("Window", control.window_enabled),
+                RegisterField::new(
+                    "Tile data",
+                    if control.tile_data_select {
+                        "8000"
+                    } else {
+                        "8800"
+                    },
+                ),
+                RegisterField::new("BG map", map(control.background_tile_map)),
+                RegisterField::new(
+                    "OBJ size",
+                    if control.sprite_double_size {
+                        "8x16"
+                    } else {
+                        "8x8"
+                    },
+                ),
+                RegisterField::flag("OBJ", control.sprite_enabled),
+                RegisterField::flag("BG/Window", control.background_window_enabled),
+            ]
+        },
+        0xFF41 => {
+            let status: LcdStatusFlags = value.into();
+            vec![
+                RegisterField::flag("LYC int", status.int_lyc_enabled),
+                RegisterField::flag("Mode 2 int", status.int_mode_2_enabled),
+                RegisterField::flag("Mode 1 int", status.int_mode_1_enabled),
+                RegisterField::flag("Mode 0 int", status.int_mode_0_enabled),
+                RegisterField::new("LYC=LY", bit!(value: u8, 2) as u8),
+                RegisterField::new("Mode", format!("{:?}", status.ppu_mode)),
+            ]
+        },
+        0xFF47..=0xFF49 => decode_palette(value),
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::setup_default_logger;
+
+    use super::*;
+
+    fn field(fields: &[RegisterField], name: &str) -> String {
+        fields
+            .iter()
+            .find(|field| field.name == name)
+            .unwrap()
+            .value
+            .clone()
+    }
+
+    #[test]
+    fn test_io_register_name() {
+        let _guard = setup_default_logger();
+
+        assert_eq!(io_register_name(0xFF40), Some("LCDC"));
+        assert_eq!(io_register_name(0xFFFF), Some("IE"));
+        assert_eq!(io_register_name(0xFF03), None);
+        assert_eq!(io_register_name(0xFF3A), Some("WAVEA"));
+        assert_eq!(io_register_name(0xFF4F), Some("VBK"));
+    }
+
+    #[test]
+    fn test_decode_io_register() {
+        let _guard = setup_default_logger();
+
+        let lcdc = decode_io_register(0xFF40, 0x91);
+        assert_eq!(field(&lcdc, "LCD"), "on");
+        assert_eq!(field(&lcdc, "Tile data"), "8000");
+        assert_eq!(field(&lcdc, "BG map"), "9800");
+        assert_eq!(field(&lcdc, "OBJ size"), "8x8");
+
+        let stat = decode_io_register(0xFF41, 0x86);
+        assert_eq!(field(&stat, "Mode"), "OamScan");
+        assert_eq!(field(&stat, "LYC=LY"), "1");
+
+        let tac = decode_io_register(0xFF07, 0xFD);
+        assert_eq!(field(&tac, "Enabled"), "on");
+        assert_eq!(field(&tac, "Frequency"), "262144 Hz");
+
+        // directions selected with right and up held
+        let joypad = decode_io_register(0xFF00, 0b1110_1010);
+        assert_eq!(field(&joypad, "Directions"), "on");
+        assert_eq!(field(&joypad, "Buttons"), "off");
+        assert_eq!(field(&joypad, "Pressed"), "Right Up");
+
+        assert_eq!(
+            decode_io_register(0xFFFF, 0b0000_0101)
+                .iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<_>>(),
+            [
+                "VBlank: on",
+                "LCD: off",
+                "Timer: on",
+                "Serial: off",
+                "Joypad: off"
+            ]
+        );
+        assert_eq!(field(&decode_io_register(0xFF47, 0xE4), "Color 3"), "3");
+        assert!(decode_io_register(0xFF42, 0x12).is_empty());
+    }
+}
@@ -1,6 +1,9 @@
+use std::str::FromStr;
+
 const SELECT_BUTTONS_BIT: usize = 5;
 const SELECT_DIRECTIONS_BIT: usize = 4;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Key {
     Down,
     Up,
@@ -12,6 +15,24 @@ pub enum Key {
     Start,
 }
 
+impl FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "down" => Key::Down,
+            "up" => Key::Up,
+            "left" => Key::Left,
+            "right" => Key::Right,
+            "b" => Key::B,
+            "a" => Key::A,
+            "select" => Key::Select,
+            "start" => Key::Start,
+            _ => return Err(format!("Unknown key '{}'", s)),
+        })
+    }
+}
+
 #[derive(Default, Clone, Copy)]
 pub struct JoypadRegister {
     interrupt: bool,
@@ -4,27 +4,93 @@
 
 mod cartridge;
 mod cpu;
+mod debugger;
+mod disassembler;
 mod emulator;
+mod gdb;
 mod graphics;
+mod io_registers;
 mod joypad;
 mod memory;
+mod model;
 mod serial;
 mod system;
 mod timer;
+mod trace;
 
 pub mod utils;
 
 pub mod prelude {
+    pub use super::cartridge::CartridgeHeader;
+    pub use super::cartridge::CgbSupport;
+    pub use super::cartridge::Checksum;
+    pub use super::cartridge::Destination;
     pub use super::cpu::Cpu;
+    pub use super::cpu::bus::Bus;
+    pub use super::cpu::interrupts::Interrupt;
     pub use super::cpu::registers::Registers;
+    pub use super::debugger::Access;
+    pub use super::debugger::Breakpoint;
+    pub use super::debugger::Comparison;
+    pub use super::debugger::Condition;
+    pub use super::debugger::DebugRegister;
+    pub use super::debugger::Debugger;
+    pub use super::debugger::RunTarget;
+    pub use super::debugger::StopReason;
+    pub use super::debugger::Watchpoint;
+    pub use super::disassembler::DisassembledInstruction;
+    pub use super::disassembler::SymbolError;
+    pub use super::disassembler::Symbols;
+    pub use super::disassembler::disassemble;
+    pub use super::disassembler::disassemble_range;
     pub use super::emulator::Emulator;
+    pub use super::emulator::ExecutionError;
+    pub use super::emulator::LoadError;
+    pub use super::gdb::GdbServer;
+    pub use super::gdb::GdbStub;
+    pub use super::gdb::Response;
+    pub use super::gdb::Resume;
+    pub use super::graphics::ObjectInfo;
+    pub use super::graphics::object::Object;
+    pub use super::graphics::object::SpriteFlags;
+    pub use super::graphics::screenshot::GRAYSCALE_PALETTE;
+    pub use super::graphics::screenshot::Palette;
+    pub use super::graphics::screenshot::Screenshot;
+    pub use super::graphics::tile::IDENTITY_PALETTE;
     pub use super::graphics::tile::Pixel;
+    pub use super::graphics::tile::TILE_MAP_WIDTH;
+    pub use super::graphics::tile::TileData;
+    pub use super::graphics::tile::TileMap;
+    pub use super::io_registers::IO_REGISTERS;
+    pub use super::io_registers::RegisterField;
+    pub use super::io_registers::decode_io_register;
+    pub use super::io_registers::io_register_name;
+    pub use super::memory::MemoryRegion;
     pub use super::memory::mbc::Mbc;
     pub use super::memory::mbc::Mbc0;
     pub use super::memory::mbc::Mbc1;
+    pub use super::memory::mbc::MbcType;
+    pub use super::memory::{
+        E_RAM_BANK_ADDR, ECHO_RAM_ADDR, H_RAM_ADDR, IE_REGISTER_ADDR, IO_REGISTERS_ADDR, OAM_ADDR,
+        ROM_BANK_0_ADDR, ROM_BANK_X_ADDR, TILE_MAPS_ADDR, UNUSABLE_ADDR, V_RAM_ADDR,
+        W_RAM_BANK_0_ADDR, W_RAM_BANK_X_ADDR,
+    };
+    pub use super::model::Model;
+    pub use super::serial::LogSerial;
+    pub use super::serial::Serial;
+    pub use super::serial::capture::CaptureSerial;
+    pub use super::serial::capture::SerialAwaitError;
+    pub use super::serial::capture::SerialCapture;
+    pub use super::serial::link::LinkCable;
+    pub use super::serial::link::LinkSerial;
+    pub use super::serial::printer::Printer;
+    pub use super::serial::printer::PrinterImage;
     pub use super::system::System;
+    pub use super::trace::TraceEntry;
+    pub use super::trace::TraceFormat;
+    pub use super::trace::TraceRecorder;
 
-    pub use super::graphics::{LCD_HEIGHT, LCD_WIDTH};
+    pub use super::graphics::{FRAME_CYCLES, LCD_HEIGHT, LCD_WIDTH, NUM_OBJECTS};
     pub use super::joypad::Key;
 }
 
@@ -10,19 +10,90 @@ pub(super) const UNUSABLE_SIZE: usize = 0x0060;
 pub(super) const IO_REGISTERS_SIZE: usize = 0x0080;
 pub(super) const H_RAM_SIZE: usize = 0x007F;
 
-pub(super) const ROM_BANK_0_ADDR: u16 = 0x0000;
-pub(super) const ROM_BANK_X_ADDR: u16 = ROM_BANK_0_ADDR + (ROM_BANK_SIZE as u16);
-pub(super) const V_RAM_ADDR: u16 = ROM_BANK_X_ADDR + (ROM_BANK_SIZE as u16);
-pub(super) const TILE_MAPS_ADDR: u16 = V_RAM_ADDR + 0x1800;
-pub(super) const E_RAM_BANK_ADDR: u16 = V_RAM_ADDR + (V_RAM_BANK_SIZE as u16);
-pub(super) const W_RAM_BANK_0_ADDR: u16 = E_RAM_BANK_ADDR + (E_RAM_BANK_SIZE as u16);
-pub(super) const W_RAM_BANK_X_ADDR: u16 = W_RAM_BANK_0_ADDR + (W_RAM_BANK_SIZE as u16);
-pub(super) const ECHO_RAM_ADDR: u16 = W_RAM_BANK_X_ADDR + (W_RAM_BANK_SIZE as u16);
-pub(super) const OAM_ADDR: u16 = ECHO_RAM_ADDR + (ECHO_RAM_SIZE as u16);
-pub(super) const UNUSABLE_ADDR: u16 = OAM_ADDR + (OAM_SIZE as u16);
-pub(super) const IO_REGISTERS_ADDR: u16 = UNUSABLE_ADDR + (UNUSABLE_SIZE as u16);
-pub(super) const H_RAM_ADDR: u16 = IO_REGISTERS_ADDR + (IO_REGISTERS_SIZE as u16);
-pub(super) const IE_REGISTER_ADDR: u16 = H_RAM_ADDR + (H_RAM_SIZE as u16);
+pub const ROM_BANK_0_ADDR: u16 = 0x0000;
+pub const ROM_BANK_X_ADDR: u16 = ROM_BANK_0_ADDR + (ROM_BANK_SIZE as u16);
+pub const V_RAM_ADDR: u16 = ROM_BANK_X_ADDR + (ROM_BANK_SIZE as u16);
+pub const TILE_MAPS_ADDR: u16 = V_RAM_ADDR + 0x1800;
+pub const E_RAM_BANK_ADDR: u16 = V_RAM_ADDR + (V_RAM_BANK_SIZE as u16);
+pub const W_RAM_BANK_0_ADDR: u16 = E_RAM_BANK_ADDR + (E_RAM_BANK_SIZE as u16);
+pub const W_RAM_BANK_X_ADDR: u16 = W_RAM_BANK_0_ADDR + (W_RAM_BANK_SIZE as u16);
+pub const ECHO_RAM_ADDR: u16 = W_RAM_BANK_X_ADDR + (W_RAM_BANK_SIZE as u16);
+pub const OAM_ADDR: u16 = ECHO_RAM_ADDR + (ECHO_RAM_SIZE as u16);
+pub const UNUSABLE_ADDR: u16 = OAM_ADDR + (OAM_SIZE as u16);
+pub const IO_REGISTERS_ADDR: u16 = UNUSABLE_ADDR + (UNUSABLE_SIZE as u16);
+pub const H_RAM_ADDR: u16 = IO_REGISTERS_ADDR + (IO_REGISTERS_SIZE as u16);
+pub const IE_REGISTER_ADDR: u16 = H_RAM_ADDR + (H_RAM_SIZE as u16);
+
+pub(super) const CGB_FLAG_ADDR: u16 = 0x0143;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MemoryRegion {
+    Rom0,
+    RomX,
+    VRam,
+    SRam,
+    WRam0,
+    WRamX,
+    Echo,
+    Oam,
+    Unusable,
+    Io,
+    HRam,
+    Ie,
+}
+
+impl MemoryRegion {
+    pub fn from_address(address: u16) -> Self {
+        match address {
+            ROM_BANK_0_ADDR..ROM_BANK_X_ADDR => Self::Rom0,
+            ROM_BANK_X_ADDR..V_RAM_ADDR => Self::RomX,
+            V_RAM_ADDR..E_RAM_BANK_ADDR => Self::VRam,
+            E_RAM_BANK_ADDR..W_RAM_BANK_0_ADDR => Self::SRam,
+            W_RAM_BANK_0_ADDR..W_RAM_BANK_X_ADDR => Self::WRam0,
+            W_RAM_BANK_X_ADDR..ECHO_RAM_ADDR => Self::WRamX,
+            ECHO_RAM_ADDR..OAM_ADDR => Self::Echo,
+            OAM_ADDR..UNUSABLE_ADDR => Self::Oam,
+            UNUSABLE_ADDR..IO_REGISTERS_ADDR => Self::Unusable,
+            IO_REGISTERS_ADDR..H_RAM_ADDR => Self::Io,
+            H_RAM_ADDR..IE_REGISTER_ADDR => Self::HRam,
+            IE_REGISTER_ADDR..=u16::MAX => Self::Ie,
+        }
+    }
+
+    pub fn start(&self) -> u16 {
+        match self {
+            Self::Rom0 => ROM_BANK_0_ADDR,
+            Self::RomX => ROM_BANK_X_ADDR,
+            Self::VRam => V_RAM_ADDR,
+            Self::SRam => E_RAM_BANK_ADDR,
+            Self::WRam0 => W_RAM_BANK_0_ADDR,
+            Self::WRamX => W_RAM_BANK_X_ADDR,
+            Self::Echo => ECHO_RAM_ADDR,
+            Self::Oam => OAM_ADDR,
+            Self::Unusable => UNUSABLE_ADDR,
+            Self::Io => IO_REGISTERS_ADDR,
+            Self::HRam => H_RAM_ADDR,
+            Self::Ie => IE_REGISTER_ADDR,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Rom0 => "ROM0",
+            Self::RomX => "ROMX",
+            Self::VRam => "VRAM",
+            Self::SRam => "SRAM",
+            Self::WRam0 => "WRAM0",
+            Self::WRamX => "WRAMX",
+            Self::Echo => "ECHO",
+            Self::Oam => "OAM",
+            Self::Unusable => "UNUSED",
+            Self::Io => "IO",
+            Self::HRam => "HRAM",
+            Self::Ie => "IE",
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -43,4 +114,25 @@ mod tests {
         assert_eq!(H_RAM_ADDR, 0xFF80);
         assert_eq!(IE_REGISTER_ADDR, 0xFFFF);
     }
+
+    #[test]
+    fn test_memory_regions() {
+        assert_eq!(MemoryRegion::from_address(0x0000), MemoryRegion::Rom0);
+        assert_eq!(MemoryRegion::from_address(0x7FFF), MemoryRegion::RomX);
+        assert_eq!(MemoryRegion::from_address(0x9800), MemoryRegion::VRam);
+        assert_eq!(MemoryRegion::from_address(0xA000), MemoryRegion::SRam);
+        assert_eq!(MemoryRegion::from_address(0xD000), MemoryRegion::WRamX);
+        assert_eq!(MemoryRegion::from_address(0xFE9F), MemoryRegion::Oam);
+        assert_eq!(MemoryRegion::from_address(0xFF44), MemoryRegion::Io);
+        assert_eq!(MemoryRegion::from_address(0xFFFE), MemoryRegion::HRam);
+        assert_eq!(MemoryRegion::from_address(0xFFFF), MemoryRegion::Ie);
+
+        // every region starts where the previous one ends
+        for address in 1..=0xFFFF {
+            let region = MemoryRegion::from_address(address);
+            if region != MemoryRegion::from_address(address - 1) {
+                assert_eq!(region.start(), address);
+            }
+        }
+    }
 }
@@ -5,16 +5,20 @@ pub use mbc0::Mbc0;
 pub use mbc1::Mbc1;
 
 use crate::cartridge::CartridgeHeader;
+use crate::emulator::LoadError;
+use crate::memory::ROM_BANK_SIZE;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MbcType {
     Mbc0,
     Mbc1 { ram: bool, battery: bool },
 }
 
-impl From<u8> for MbcType {
-    fn from(value: u8) -> Self {
-        match value {
+impl TryFrom<u8> for MbcType {
+    type Error = LoadError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
             0x00 => MbcType::Mbc0,
             0x01 => MbcType::Mbc1 {
                 ram: false,
@@ -25,11 +29,15 @@ impl From<u8> for MbcType {
                 battery: false,
             },
             0x03 => MbcType::Mbc1 {
-                ram: false,
+                ram: true,
                 battery: true,
             },
-            _ => panic!("Unknown MBC type {}", value),
-        }
+            _ => {
+                return Err(LoadError::UnsupportedMapper {
+                    cartridge_type: value,
+                });
+            },
+        })
     }
 }
 
@@ -39,14 +47,31 @@ pub trait Mbc {
 
     fn read_ram(&self, address: u16) -> u8;
     fn write_ram(&mut self, address: u16, value: u8);
-}
 
-pub struct CreateError;
+    // ROM bank currently mapped at the address
+    fn rom_bank(&self, address: u16) -> usize {
+        address as usize / ROM_BANK_SIZE
+    }
 
-pub fn new_mbc_from_buffer(buffer: Vec<u8>) -> Result<Box<dyn Mbc>, String> {
-    let header = Into::<CartridgeHeader>::into(&buffer);
+    // RAM bank currently mapped to the external RAM area
+    fn ram_bank(&self) -> usize {
+        0
+    }
+}
+
+pub fn new_mbc_from_buffer(
+    buffer: Vec<u8>,
+    header: &CartridgeHeader,
+) -> Result<Box<dyn Mbc>, LoadError> {
+    let rom_size = header.rom_banks * ROM_BANK_SIZE;
+    if buffer.len() < rom_size {
+        return Err(LoadError::TruncatedRom {
+            expected: rom_size,
+            actual: buffer.len(),
+        });
+    }
 
-    Ok(match header.mbc_type {
+    Ok(match header.mbc_type()? {
         MbcType::Mbc0 => Box::new(Mbc0::new_from_buffer(buffer)?),
         MbcType::Mbc1 { battery, .. } => {
             Box::new(Mbc1::new_from_buffer(buffer, header.ram_banks, battery)?)
@@ -1,3 +1,5 @@
+use crate::emulator::LoadError;
+
 use super::Mbc;
 
 const ROM_SIZE: usize = 0x8000;
@@ -10,17 +12,24 @@ pub struct Mbc0 {
 
 impl Mbc0 {
     #[allow(clippy::new_without_default)]
-    pub fn new() -> Result<Self, String> {
+    pub fn new() -> Result<Self, LoadError> {
         Self::new_from_buffer(vec![0; ROM_SIZE])
     }
 
-    pub fn new_from_buffer(buffer: Vec<u8>) -> Result<Self, String> {
+    pub fn new_from_buffer(buffer: Vec<u8>) -> Result<Self, LoadError> {
+        if buffer.len() < ROM_SIZE {
+            return Err(LoadError::TruncatedRom {
+                expected: ROM_SIZE,
+                actual: buffer.len(),
+            });
+        }
+
         if buffer.len() != ROM_SIZE {
-            return Err(format!(
-                "The ROM buffer has to be {} bytes big. Got: {}",
-                ROM_SIZE,
-                buffer.len()
-            ));
+            return Err(LoadError::SizeMismatch {
+                name: "ROM",
+                expected: ROM_SIZE,
+                actual: buffer.len(),
+            });
         }
 
         Ok(Mbc0 {
@@ -1,3 +1,4 @@
+use crate::emulator::LoadError;
 use crate::memory::{E_RAM_BANK_SIZE, ROM_BANK_SIZE};
 
 use super::Mbc;
@@ -24,7 +25,7 @@ impl Mbc1 {
         num_rom_banks: usize,
         num_ram_banks: usize,
         _has_battery: bool,
-    ) -> Result<Self, String> {
+    ) -> Result<Self, LoadError> {
         Self::new_from_buffer(
             vec![0; num_rom_banks * ROM_BANK_SIZE],
             num_ram_banks,
@@ -36,31 +37,30 @@ impl Mbc1 {
         buffer: Vec<u8>,
         num_ram_banks: usize,
         _has_battery: bool,
-    ) -> Result<Self, String> {
-        if buffer.len() % ROM_BANK_SIZE != 0 {
-            return Err("The ROM buffer is not a multiple of the ROM bank size".to_owned());
+    ) -> Result<Self, LoadError> {
+        if !buffer.len().is_multiple_of(ROM_BANK_SIZE) {
+            return Err(LoadError::SizeMismatch {
+                name: "ROM",
+                expected: buffer.len().next_multiple_of(ROM_BANK_SIZE),
+                actual: buffer.len(),
+            });
         }
 
         let rom_banks = buffer.len() / ROM_BANK_SIZE;
-
-        if !ALLOWED_ROM_BANKS.contains(&rom_banks) {
-            return Err(format!(
-                "Mbc1 does not support ROM buffers with size {} bytes ({} banks)",
-                buffer.len(),
-                rom_banks
-            ));
-        }
-
-        if !ALLOWED_RAM_BANKS.contains(&num_ram_banks) {
-            return Err(format!(
-                "Mbc1 does not support RAM with size {} bytes",
-                num_ram_banks
-            ));
+        let unsupported_layout = LoadError::UnsupportedMemoryLayout {
+            mapper: "Mbc1",
+            rom_banks,
+            ram_banks: num_ram_banks,
+        };
+
+        if !ALLOWED_ROM_BANKS.contains(&rom_banks) || !ALLOWED_RAM_BANKS.contains(&num_ram_banks) {
+            return Err(unsupported_layout);
         }
 
+        // Mbc1 with ROM size >512 KiB needs the RAM bank bits for the ROM, so at most 8 KiB of RAM
         let extra_large_rom = rom_banks > 32;
-        if extra_large_rom && num_ram_banks != 1 {
-            panic!("Mbc1 with ROM size >512 KiB only supports RAM with size 8 KiB");
+        if extra_large_rom && num_ram_banks > 1 {
+            return Err(unsupported_layout);
         }
 
         Ok(Mbc1 {
@@ -82,42 +82,21 @@ impl Mbc for Mbc1 {
     fn read_rom(&self, address: u16) -> u8 {
         assert!((address as usize) < 2 * ROM_BANK_SIZE);
 
-        if (address as usize) < ROM_BANK_SIZE {
-            // ROM bank 0
-            self.rom[address as usize]
-        } else {
-            // ROM bank 1-127
-            let bit_mask = (self.num_rom_banks - 1) & 0b11111;
-            let mut bank = (self.rom_bank_number as usize) & bit_mask;
-            if bank == 0 {
-                bank = 1
-            }
-            let real_address = (bank * ROM_BANK_SIZE) + ((address as usize) % ROM_BANK_SIZE);
-
-            if self.num_rom_banks > 32 {
-                todo!("Extra large Mbc1 ROM");
-            }
-
-            self.rom[real_address]
-        }
+        let real_address =
+            (self.rom_bank(address) * ROM_BANK_SIZE) + ((address as usize) % ROM_BANK_SIZE);
+        self.rom[real_address]
     }
+
     fn write_rom(&mut self, address: u16, value: u8) {
         assert!((address as usize) < 2 * ROM_BANK_SIZE);
 
         if address < 0x2000 {
             self.ram_enabled = value & 0x0F == 0x0A;
+        } else if address < 0x4000 {
+            self.rom_bank_number = value & 0b1_1111;
         } else if address < 0x6000 {
-            if !self.num_rom_banks > 32 {
-                if address < 0x4000 {
-                    self.rom_bank_number = value;
-                }
-                if address < 0x6000 {
-                    self.ram_bank_number = value;
-                }
-            } else {
-                todo!("Extra large Mbc1 ROM");
-            }
-        } else if address < 0x8000 {
+            self.ram_bank_number = value & 0b11;
+        } else {
             self.banking_mode_advanced = (value & 0x01) > 0;
         }
     }
@@ -125,33 +104,54 @@ impl Mbc for Mbc1 {
     fn read_ram(&self, address: u16) -> u8 {
         assert!((address as usize) < E_RAM_BANK_SIZE);
 
-        if !self.ram_enabled {
+        if !self.ram_enabled || self.ram.is_empty() {
             return 0xFF;
         }
 
-        let real_address =
-            (((self.ram_bank_number & 0b11) as usize) * E_RAM_BANK_SIZE) + (address as usize);
-        if self.num_rom_banks > 32 {
-            todo!("Extra larg Mbc1 ROM");
-        }
-
-        self.ram[real_address]
+        let real_address = (self.ram_bank() * E_RAM_BANK_SIZE) + (address as usize);
+        // smaller RAM chips only decode the lower address lines
+        self.ram[real_address % self.ram.len()]
     }
 
     fn write_ram(&mut self, address: u16, value: u8) {
         assert!((address as usize) < E_RAM_BANK_SIZE);
 
-        if !self.ram_enabled {
+        if !self.ram_enabled || self.ram.is_empty() {
             return;
         }
 
-        let real_address =
-            (((self.ram_bank_number & 0b11) as usize) * E_RAM_BANK_SIZE) + (address as usize);
-        if self.num_rom_banks > 32 {
-            todo!("Extra larg Mbc1 ROM");
-        }
+        let real_address = (self.ram_bank() * E_RAM_BANK_SIZE) + (address as usize);
+        let ram_size = self.ram.len();
+        self.ram[real_address % ram_size] = value;
+    }
+
+    fn rom_bank(&self, address: u16) -> usize {
+        // ROMs larger than 512 KiB use the RAM bank register as bits 5 and 6 of the ROM bank
+        let upper_bits = (self.ram_bank_number as usize) << 5;
+
+        let bank = if (address as usize) < ROM_BANK_SIZE {
+            // the bank 0 area is only switched in advanced banking mode
+            if self.banking_mode_advanced {
+                upper_bits
+            } else {
+                0
+            }
+        } else {
+            // bank 0 can't be selected for the switchable area, a larger ROM maps 0x21 etc. instead
+            let lower_bits = self.rom_bank_number as usize;
+            upper_bits | if lower_bits == 0 { 1 } else { lower_bits }
+        };
+
+        bank & (self.num_rom_banks - 1)
+    }
 
-        self.ram[real_address] = value;
+    fn ram_bank(&self) -> usize {
+        // RAM banking is only enabled in advanced banking mode
+        if self.banking_mode_advanced {
+            self.ram_bank_number as usize
+        } else {
+            0
+        }
     }
 }
 
@@ -216,8 +216,9 @@ mod test {
 
         // ram disabled
         assert_eq!(mbc.read_ram(0x0000), 0xFF);
-        // enable ram
+        // enable ram and ram banking
         mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x6000, 0x01);
 
         // bank 0
         assert_eq!(mbc.read_ram(0x0000), 1);
@@ -236,4 +237,90 @@ mod test {
         assert_eq!(mbc.read_ram(0x0000), 2);
         assert_eq!(mbc.read_ram(0x0001), 2);
     }
+
+    #[test]
+    fn test_ram_banking_mode() {
+        let _guard = setup_default_logger();
+
+        let mut mbc = Mbc1::new(2, 4, false).unwrap();
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_rom(0x4000, 0x02);
+
+        // simple banking mode locks the RAM to bank 0
+        assert_eq!(mbc.ram_bank(), 0);
+        mbc.write_ram(0x0000, 1);
+        assert_eq!(mbc.ram[0x0000], 1);
+        assert_eq!(mbc.ram[0x4000], 0);
+
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.ram_bank(), 2);
+        mbc.write_ram(0x0000, 2);
+        assert_eq!(mbc.ram[0x4000], 2);
+        assert_eq!(mbc.read_ram(0x0000), 2);
+
+        mbc.write_rom(0x6000, 0x00);
+        assert_eq!(mbc.read_ram(0x0000), 1);
+    }
+
+    #[test]
+    fn test_rom_large() {
+        let _guard = setup_default_logger();
+
+        assert!(Mbc1::new(128, 0, false).is_ok());
+        let mut mbc = Mbc1::new(64, 1, false).unwrap();
+        mbc.rom[32 * ROM_BANK_SIZE] = 0x20;
+        mbc.rom[33 * ROM_BANK_SIZE] = 0x21;
+        mbc.rom[34 * ROM_BANK_SIZE] = 0x22;
+
+        // bank 0x20 is not selectable for the switchable area
+        mbc.write_rom(0x4000, 0x01);
+        assert_eq!(mbc.read_rom(0x4000), 0x21);
+        mbc.write_rom(0x2000, 0x02);
+        assert_eq!(mbc.read_rom(0x4000), 0x22);
+
+        // advanced banking mode also switches the bank 0 area
+        assert_eq!(mbc.read_rom(0x0000), 0x00);
+        mbc.write_rom(0x6000, 0x01);
+        assert_eq!(mbc.rom_bank(0x0000), 32);
+        assert_eq!(mbc.read_rom(0x0000), 0x20);
+
+        // the single RAM bank is used regardless of the upper bank bits
+        mbc.write_rom(0x0000, 0x0A);
+        mbc.write_ram(0x0000, 0x42);
+        mbc.write_rom(0x4000, 0x00);
+        assert_eq!(mbc.read_ram(0x0000), 0x42);
+    }
+
+    #[test]
+    fn test_ram_missing() {
+        let _guard = setup_default_logger();
+
+        let mut mbc = Mbc1::new(2, 0, false).unwrap();
+        mbc.write_rom(0x0000, 0x0A);
+
+        mbc.write_ram(0x0000, 1);
+        assert_eq!(mbc.read_ram(0x0000), 0xFF);
+    }
+
+    #[test]
+    fn test_unsupported_layout() {
+        let _guard = setup_default_logger();
+
+        assert!(matches!(
+            Mbc1::new(3, 0, false),
+            Err(LoadError::UnsupportedMemoryLayout { rom_banks: 3, .. })
+        ));
+        assert!(matches!(
+            Mbc1::new(64, 4, false),
+            Err(LoadError::UnsupportedMemoryLayout {
+                rom_banks: 64,
+                ram_banks: 4,
+                ..
+            })
+        ));
+        assert!(matches!(
+            Mbc1::new_from_buffer(vec![0; ROM_BANK_SIZE + 1], 0, false),
+            Err(LoadError::SizeMismatch { .. })
+        ));
+    }
 }
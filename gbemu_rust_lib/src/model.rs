@@ -0,0 +1,249 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::cpu::registers::Registers;
+use crate::emulator::LoadError;
+
+const DMG_BOOT_ROM_SIZE: usize = 0x0100;
+const CGB_BOOT_ROM_SIZE: usize = 0x0900;
+
+const DMG_W_RAM_BANKS: usize = 2;
+const CGB_W_RAM_BANKS: usize = 8;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Model {
+    Dmg0,
+    #[default]
+    Dmg,
+    Mgb,
+    Sgb,
+    Sgb2,
+    Cgb,
+    Agb,
+}
+
+impl Model {
+    pub const ALL: [Model; 7] = [
+        Model::Dmg0,
+        Model::Dmg,
+        Model::Mgb,
+        Model::Sgb,
+        Model::Sgb2,
+        Model::Cgb,
+        Model::Agb,
+    ];
+
+    pub fn is_cgb(&self) -> bool {
+        matches!(self, Model::Cgb | Model::Agb)
+    }
+
+    pub fn w_ram_banks(&self) -> usize {
+        if self.is_cgb() {
+            CGB_W_RAM_BANKS
+        } else {
+            DMG_W_RAM_BANKS
+        }
+    }
+
+    // Work RAM is not cleared on power-up. The DMG family starts with (deterministic) noise
+    // while the CGB family starts zeroed.
+    pub fn initial_w_ram(&self, size: usize) -> Vec<u8> {
+        if self.is_cgb() {
+            return vec![0x00; size];
+        }
+
+        let mut state: u32 = 0x4742_0000 | (*self as u32);
+        (0..size)
+            .map(|_| {
+                // xorshift32
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                (state >> 24) as u8
+            })
+            .collect()
+    }
+
+    // Reading from 0xFEA0-0xFEFF returns 0x00 on most models, the AGB returns the high nibble
+    // of the lower address byte twice
+    pub fn unusable_memory_read(&self, address: u16) -> u8 {
+        match self {
+            Model::Agb => {
+                let nibble = (address as u8) & 0xF0;
+                nibble | (nibble >> 4)
+            },
+            _ => 0x00,
+        }
+    }
+
+    pub fn boot_rom_size(&self) -> usize {
+        if self.is_cgb() {
+            CGB_BOOT_ROM_SIZE
+        } else {
+            DMG_BOOT_ROM_SIZE
+        }
+    }
+
+    pub fn post_boot_registers(&self, header_checksum: u8) -> Registers {
+        // the DMG and MGB boot ROMs leave the H and C flags set if the header checksum is non-zero
+        let dmg_flags = if header_checksum == 0x00 {
+            0b1000_0000
+        } else {
+            0b1011_0000
+        };
+
+        let (a, f, b, c, d, e, h, l) = match self {
+            Model::Dmg0 => (0x01, 0x00, 0xFF, 0x13, 0x00, 0xC1, 0x84, 0x03),
+            Model::Dmg => (0x01, dmg_flags, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D),
+            Model::Mgb => (0xFF, dmg_flags, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D),
+            Model::Sgb => (0x01, 0x00, 0x00, 0x14, 0x00, 0x00, 0xC0, 0x60),
+            Model::Sgb2 => (0xFF, 0x00, 0x00, 0x14, 0x00, 0x00, 0xC0, 0x60),
+            Model::Cgb => (0x11, 0x80, 0x00, 0x00, 0xFF, 0x56, 0x00, 0x0D),
+            Model::Agb => (0x11, 0x00, 0x01, 0x00, 0xFF, 0x56, 0x00, 0x0D),
+        };
+
+        Registers {
+            a,
+            f,
+            b,
+            c,
+            d,
+            e,
+            h,
+            l,
+            w: 0x00,
+            z: 0x00,
+            pc: 0x0100,
+            sp: 0xFFFE,
+            cc: false,
+        }
+    }
+
+    // The SGB and CGB values depend on how long the boot ROM ran and are approximations
+    pub fn post_boot_system_counter(&self) -> u16 {
+        match self {
+            Model::Dmg0 => 0x1800,
+            Model::Dmg | Model::Mgb => 0xAB00,
+            Model::Sgb | Model::Sgb2 => 0xD800,
+            Model::Cgb | Model::Agb => 0x2600,
+        }
+    }
+}
+
+impl Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Model::Dmg0 => "DMG0",
+            Model::Dmg => "DMG",
+            Model::Mgb => "MGB",
+            Model::Sgb => "SGB",
+            Model::Sgb2 => "SGB2",
+            Model::Cgb => "CGB",
+            Model::Agb => "AGB",
+        })
+    }
+}
+
+impl FromStr for Model {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Model::ALL
+            .into_iter()
+            .find(|model| model.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown model '{}'", s))
+    }
+}
+
+pub struct BootRom {
+    data: Vec<u8>,
+}
+
+impl BootRom {
+    pub fn new_from_buffer(buffer: Vec<u8>, model: Model) -> Result<Self, LoadError> {
+        if buffer.len() != model.boot_rom_size() {
+            return Err(LoadError::SizeMismatch {
+                name: "boot ROM",
+                expected: model.boot_rom_size(),
+                actual: buffer.len(),
+            });
+        }
+
+        Ok(BootRom { data: buffer })
+    }
+
+    pub fn read(&self, address: u16) -> Option<u8> {
+        match address {
+            // the cartridge header stays visible on the CGB
+            0x0100..0x0200 => None,
+            _ => self.data.get(address as usize).copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_post_boot_registers() {
+        let registers = Model::Dmg.post_boot_registers(0x00);
+        assert_eq!(registers.get_af(), 0x0180);
+        assert_eq!(registers.get_bc(), 0x0013);
+        assert_eq!(registers.get_de(), 0x00D8);
+        assert_eq!(registers.get_hl(), 0x014D);
+        assert_eq!(registers.sp, 0xFFFE);
+        assert_eq!(registers.pc, 0x0100);
+
+        assert_eq!(Model::Dmg.post_boot_registers(0x42).get_af(), 0x01B0);
+        assert_eq!(Model::Mgb.post_boot_registers(0x42).get_af(), 0xFFB0);
+        assert_eq!(Model::Dmg0.post_boot_registers(0x42).get_bc(), 0xFF13);
+        assert_eq!(Model::Sgb2.post_boot_registers(0x42).get_hl(), 0xC060);
+        assert_eq!(Model::Cgb.post_boot_registers(0x42).get_af(), 0x1180);
+        assert_eq!(Model::Agb.post_boot_registers(0x42).get_bc(), 0x0100);
+    }
+
+    #[test]
+    fn test_initial_w_ram() {
+        let w_ram = Model::Dmg.initial_w_ram(0x2000);
+        assert_eq!(w_ram.len(), 0x2000);
+        assert!(w_ram.iter().any(|b| *b != 0x00));
+        assert_eq!(w_ram, Model::Dmg.initial_w_ram(0x2000));
+        assert_ne!(w_ram, Model::Mgb.initial_w_ram(0x2000));
+
+        assert!(Model::Cgb.initial_w_ram(0x8000).iter().all(|b| *b == 0x00));
+    }
+
+    #[test]
+    fn test_unusable_memory_read() {
+        assert_eq!(Model::Dmg.unusable_memory_read(0xFEA5), 0x00);
+        assert_eq!(Model::Agb.unusable_memory_read(0xFEA5), 0xAA);
+        assert_eq!(Model::Agb.unusable_memory_read(0xFEF0), 0xFF);
+    }
+
+    #[test]
+    fn test_model_from_str() {
+        for model in Model::ALL {
+            assert_eq!(model.to_string().to_lowercase().parse::<Model>(), Ok(model));
+        }
+
+        assert!("gba".parse::<Model>().is_err());
+    }
+
+    #[test]
+    fn test_boot_rom_mapping() {
+        assert!(BootRom::new_from_buffer(vec![0; 0x0100], Model::Cgb).is_err());
+
+        let boot_rom = BootRom::new_from_buffer(vec![0x42; 0x0100], Model::Dmg).unwrap();
+        assert_eq!(boot_rom.read(0x0000), Some(0x42));
+        assert_eq!(boot_rom.read(0x00FF), Some(0x42));
+        assert_eq!(boot_rom.read(0x0100), None);
+
+        let boot_rom = BootRom::new_from_buffer(vec![0x42; 0x0900], Model::Cgb).unwrap();
+        assert_eq!(boot_rom.read(0x00FF), Some(0x42));
+        assert_eq!(boot_rom.read(0x0100), None);
+        assert_eq!(boot_rom.read(0x0200), Some(0x42));
+        assert_eq!(boot_rom.read(0x08FF), Some(0x42));
+        assert_eq!(boot_rom.read(0x0900), None);
+    }
+}
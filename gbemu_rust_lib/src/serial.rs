@@ -1,3 +1,7 @@
+pub mod capture;
+pub mod link;
+pub mod printer;
+
 use tracing::info;
 
 use crate::utils::bit_operations::bit;
@@ -10,12 +14,18 @@ pub trait Serial {
     fn set_transfer_control(&mut self, value: u8);
 
     fn get_last_buffer(&self) -> &String;
+
+    fn step(&mut self) {}
+
+    fn interrupt(&mut self) -> bool {
+        false
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy)]
-struct SerialControl {
-    enabled: bool,
-    clock_select: bool,
+pub(crate) struct SerialControl {
+    pub(crate) enabled: bool,
+    pub(crate) clock_select: bool,
 }
 
 impl From<u8> for SerialControl {
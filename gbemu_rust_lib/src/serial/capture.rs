@@ -0,0 +1,248 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tracing::info;
+
+use crate::emulator::{Emulator, ExecutionError};
+
+use super::{Serial, SerialControl};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SerialByte {
+    pub cycle: u64,
+    pub value: u8,
+}
+
+#[derive(Debug)]
+pub enum SerialAwaitError {
+    Timeout { cycles: u64 },
+    Execution(ExecutionError),
+}
+
+impl From<ExecutionError> for SerialAwaitError {
+    fn from(value: ExecutionError) -> Self {
+        Self::Execution(value)
+    }
+}
+
+#[derive(Debug, Default)]
+struct CaptureState {
+    cycle: u64,
+    bytes: Vec<SerialByte>,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SerialCapture {
+    state: Rc<RefCell<CaptureState>>,
+}
+
+impl SerialCapture {
+    pub fn cycle(&self) -> u64 {
+        self.state.borrow().cycle
+    }
+
+    pub fn bytes(&self) -> Vec<SerialByte> {
+        self.state.borrow().bytes.clone()
+    }
+
+    pub fn data(&self) -> Vec<u8> {
+        self.state.borrow().bytes.iter().map(|b| b.value).collect()
+    }
+
+    pub fn transcript(&self) -> String {
+        self.state
+            .borrow()
+            .bytes
+            .iter()
+            .map(|b| b.value as char)
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.borrow().bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.state.borrow().bytes.is_empty()
+    }
+
+    pub fn clear(&self) {
+        self.state.borrow_mut().bytes.clear();
+    }
+
+    pub fn find(&self, pattern: &[u8]) -> Option<usize> {
+        find_bytes(&self.state.borrow().bytes, pattern, 0)
+    }
+
+    pub fn contains(&self, pattern: &str) -> bool {
+        self.find(pattern.as_bytes()).is_some()
+    }
+
+    // Steps the emulator until `pattern` has been transmitted, returning the cycle at which the
+    // last byte of the pattern was sent
+    pub fn await_bytes(
+        &self,
+        emulator: &mut Emulator,
+        pattern: &[u8],
+        timeout_cycles: u64,
+    ) -> Result<u64, SerialAwaitError> {
+        if pattern.is_empty() {
+            return Ok(self.cycle());
+        }
+
+        let mut searched = 0;
+        let mut cycles = 0;
+
+        loop {
+            {
+                let state = self.state.borrow();
+                if let Some(index) = find_bytes(&state.bytes, pattern, searched) {
+                    return Ok(state.bytes[index + pattern.len() - 1].cycle);
+                }
+                searched = state.bytes.len().saturating_sub(pattern.len());
+            }
+
+            if cycles == timeout_cycles {
+                return Err(SerialAwaitError::Timeout { cycles });
+            }
+
+            emulator.step()?;
+            cycles += 1;
+        }
+    }
+
+    pub fn await_pattern(
+        &self,
+        emulator: &mut Emulator,
+        pattern: &str,
+        timeout_cycles: u64,
+    ) -> Result<u64, SerialAwaitError> {
+        self.await_bytes(emulator, pattern.as_bytes(), timeout_cycles)
+    }
+}
+
+fn find_bytes(bytes: &[SerialByte], pattern: &[u8], start: usize) -> Option<usize> {
+    if pattern.is_empty() {
+        return None;
+    }
+
+    bytes
+        .get(start..)?
+        .windows(pattern.len())
+        .position(|window| window.iter().map(|b| b.value).eq(pattern.iter().copied()))
+        .map(|position| position + start)
+}
+
+#[derive(Default)]
+pub struct CaptureSerial {
+    transfer_data: u8,
+    transfer_control: SerialControl,
+    capture: SerialCapture,
+
+    buffer: String,
+    last_buffer: String,
+}
+
+impl CaptureSerial {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn capture(&self) -> SerialCapture {
+        self.capture.clone()
+    }
+
+    fn transfer(&mut self) {
+        let mut state = self.capture.state.borrow_mut();
+        let cycle = state.cycle;
+        state.bytes.push(SerialByte {
+            cycle,
+            value: self.transfer_data,
+        });
+
+        if self.transfer_data == b'\n' {
+            info!(name: "serial::transfer", "{}", self.buffer);
+            self.last_buffer = self.buffer.clone();
+            self.buffer.clear();
+        } else {
+            self.buffer.push(self.transfer_data as char);
+        }
+
+        self.transfer_control.enabled = false;
+    }
+}
+
+impl Serial for CaptureSerial {
+    fn read(&self) -> u8 {
+        0xFF
+    }
+
+    fn write(&mut self, value: u8) {
+        self.transfer_data = value;
+    }
+
+    fn get_transfer_control(&self) -> u8 {
+        self.transfer_control.into()
+    }
+
+    fn set_transfer_control(&mut self, value: u8) {
+        self.transfer_control = value.into();
+        if self.transfer_control.enabled && self.transfer_control.clock_select {
+            self.transfer();
+        } else {
+            self.transfer_control.enabled = false;
+        }
+    }
+
+    fn get_last_buffer(&self) -> &String {
+        &self.last_buffer
+    }
+
+    fn step(&mut self) {
+        self.capture.state.borrow_mut().cycle += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(serial: &mut CaptureSerial, data: &[u8]) {
+        for byte in data {
+            serial.write(*byte);
+            serial.set_transfer_control(0x81);
+            serial.step();
+        }
+    }
+
+    #[test]
+    fn test_transcript() {
+        let mut serial = CaptureSerial::new();
+        let capture = serial.capture();
+
+        send(&mut serial, b"Hello\nWorld\n");
+
+        assert_eq!(capture.transcript(), "Hello\nWorld\n");
+        assert_eq!(serial.get_last_buffer(), "World");
+        assert_eq!(capture.len(), 12);
+        assert_eq!(
+            capture.bytes()[6],
+            SerialByte {
+                cycle: 6,
+                value: b'W'
+            }
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let mut serial = CaptureSerial::new();
+        let capture = serial.capture();
+
+        send(&mut serial, &[0x03, 0x05, 0x08, 0x0D, 0x15, 0x22]);
+
+        assert_eq!(capture.find(&[0x08, 0x0D]), Some(2));
+        assert_eq!(capture.find(&[0x42]), None);
+        assert!(!capture.contains("Passed"));
+    }
+}
@@ -0,0 +1,164 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use tracing::{debug, info};
+
+use crate::emulator::{Emulator, ExecutionError, LoadError};
+
+use super::{Serial, SerialControl};
+
+// 8192 Hz internal clock: one bit every 128 M-cycles
+const TRANSFER_CYCLES: u16 = 8 * 128;
+
+#[derive(Debug, Default)]
+struct LinkPort {
+    data: u8,
+    control: SerialControl,
+    transfer_cycle: u16,
+    interrupt: bool,
+}
+
+impl LinkPort {
+    fn complete_transfer(&mut self) {
+        self.control.enabled = false;
+        self.transfer_cycle = 0;
+        self.interrupt = true;
+    }
+}
+
+pub struct LinkSerial {
+    index: usize,
+    ports: Rc<RefCell<[LinkPort; 2]>>,
+
+    buffer: String,
+    last_buffer: String,
+}
+
+impl LinkSerial {
+    pub fn pair() -> (Self, Self) {
+        let ports = Rc::new(RefCell::new([LinkPort::default(), LinkPort::default()]));
+
+        (
+            Self {
+                index: 0,
+                ports: ports.clone(),
+                buffer: String::new(),
+                last_buffer: String::new(),
+            },
+            Self {
+                index: 1,
+                ports,
+                buffer: String::new(),
+                last_buffer: String::new(),
+            },
+        )
+    }
+
+    fn peer(&self) -> usize {
+        1 - self.index
+    }
+
+    fn log_transfer(&mut self, value: u8) {
+        if value == b'\n' {
+            info!(name: "serial::transfer", "[link {}] {}", self.index, self.buffer);
+            self.last_buffer = self.buffer.clone();
+            self.buffer.clear();
+        } else {
+            self.buffer.push(value as char);
+        }
+    }
+}
+
+impl Serial for LinkSerial {
+    fn read(&self) -> u8 {
+        self.ports.borrow()[self.index].data
+    }
+
+    fn write(&mut self, value: u8) {
+        self.ports.borrow_mut()[self.index].data = value;
+    }
+
+    fn get_transfer_control(&self) -> u8 {
+        self.ports.borrow()[self.index].control.into()
+    }
+
+    fn set_transfer_control(&mut self, value: u8) {
+        let mut ports = self.ports.borrow_mut();
+        ports[self.index].control = value.into();
+        ports[self.index].transfer_cycle = 0;
+    }
+
+    fn get_last_buffer(&self) -> &String {
+        &self.last_buffer
+    }
+
+    fn step(&mut self) {
+        let (index, peer) = (self.index, self.peer());
+
+        let sent = {
+            let mut ports = self.ports.borrow_mut();
+
+            // only the side providing the clock drives the transfer
+            let control = ports[index].control;
+            if !control.enabled || !control.clock_select {
+                return;
+            }
+
+            ports[index].transfer_cycle += 1;
+            if ports[index].transfer_cycle < TRANSFER_CYCLES {
+                return;
+            }
+
+            let sent = ports[index].data;
+            ports[index].data = ports[peer].data;
+            ports[peer].data = sent;
+
+            debug!(
+                "Link transfer: port {} sent {:02X}, received {:02X}",
+                index, sent, ports[index].data
+            );
+
+            ports[index].complete_transfer();
+            if ports[peer].control.enabled && !ports[peer].control.clock_select {
+                ports[peer].complete_transfer();
+            }
+
+            sent
+        };
+
+        self.log_transfer(sent);
+    }
+
+    fn interrupt(&mut self) -> bool {
+        let mut ports = self.ports.borrow_mut();
+        let result = ports[self.index].interrupt;
+        ports[self.index].interrupt = false;
+        result
+    }
+}
+
+pub struct LinkCable {
+    pub emulators: [Emulator; 2],
+}
+
+impl LinkCable {
+    pub fn new_from_buffers(roms: [Vec<u8>; 2], graphics_enabled: bool) -> Result<Self, LoadError> {
+        let [rom_0, rom_1] = roms;
+        let (serial_0, serial_1) = LinkSerial::pair();
+
+        Ok(Self {
+            emulators: [
+                Emulator::new_from_buffer(rom_0, graphics_enabled, None, Some(Box::new(serial_0)))?,
+                Emulator::new_from_buffer(rom_1, graphics_enabled, None, Some(Box::new(serial_1)))?,
+            ],
+        })
+    }
+
+    pub fn step(&mut self) -> Result<(), ExecutionError> {
+        for emulator in &mut self.emulators {
+            emulator.step()?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,577 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use tracing::{debug, info, warn};
+
+use crate::graphics::screenshot::{GRAYSCALE_PALETTE, encode_png};
+use crate::graphics::tile::Pixel;
+
+use super::{Serial, SerialControl};
+
+const MAGIC_BYTES: [u8; 2] = [0x88, 0x33];
+const DEVICE_ID: u8 = 0x81;
+
+const COMMAND_INIT: u8 = 0x01;
+const COMMAND_PRINT: u8 = 0x02;
+const COMMAND_DATA: u8 = 0x04;
+const COMMAND_STATUS: u8 = 0x0F;
+
+const STATUS_CHECKSUM_ERROR: u8 = 1 << 0;
+const STATUS_BUSY: u8 = 1 << 1;
+const STATUS_IMAGE_DATA_FULL: u8 = 1 << 2;
+const STATUS_UNPROCESSED_DATA: u8 = 1 << 3;
+
+pub const PRINTER_WIDTH: usize = 160;
+
+const TILE_BYTES: usize = 16;
+const TILES_PER_ROW: usize = PRINTER_WIDTH / 8;
+// one band is 2 rows of 20 tiles
+const BAND_BYTES: usize = 2 * TILES_PER_ROW * TILE_BYTES;
+const MAX_BANDS: usize = 9;
+// a margin unit is approximated with the height of one band
+const MARGIN_LINES: usize = 16;
+// number of status inquiries the printer reports busy after a print command
+const BUSY_INQUIRIES: u8 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PacketState {
+    Magic0,
+    Magic1,
+    Command,
+    Compression,
+    LengthLow,
+    LengthHigh,
+    Data,
+    ChecksumLow,
+    ChecksumHigh,
+    DeviceId,
+    Status,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrinterImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Pixel>,
+}
+
+impl PrinterImage {
+    pub fn get_pixel(&self, x: usize, y: usize) -> Pixel {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn write_png<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        encode_png(
+            BufWriter::new(File::create(path)?),
+            self.width,
+            self.height,
+            &self.pixels,
+            &GRAYSCALE_PALETTE,
+            1,
+        )
+    }
+}
+
+pub type PrinterOutput = Rc<RefCell<Vec<PrinterImage>>>;
+
+pub struct Printer {
+    transfer_data: u8,
+    transfer_control: SerialControl,
+    response: u8,
+    interrupt: bool,
+
+    state: PacketState,
+    command: u8,
+    compression: bool,
+    length: u16,
+    packet_data: Vec<u8>,
+    checksum: u16,
+    received_checksum: u16,
+
+    status: u8,
+    busy_inquiries: u8,
+    image_data: Vec<u8>,
+
+    output: PrinterOutput,
+    output_dir: Option<PathBuf>,
+
+    last_buffer: String,
+}
+
+impl Printer {
+    pub fn new() -> Self {
+        Self {
+            transfer_data: 0x00,
+            transfer_control: SerialControl::default(),
+            response: 0x00,
+            interrupt: false,
+
+            state: PacketState::Magic0,
+            command: 0x00,
+            compression: false,
+            length: 0,
+            packet_data: Vec::new(),
+            checksum: 0,
+            received_checksum: 0,
+
+            status: 0x00,
+            busy_inquiries: 0,
+            image_data: Vec::with_capacity(MAX_BANDS * BAND_BYTES),
+
+            output: Rc::new(RefCell::new(Vec::new())),
+            output_dir: None,
+
+            last_buffer: String::new(),
+        }
+    }
+
+    pub fn new_with_output_dir<P: AsRef<Path>>(path: P) -> Self {
+        let mut printer = Self::new();
+        printer.output_dir = Some(path.as_ref().to_path_buf());
+        printer
+    }
+
+    pub fn output(&self) -> PrinterOutput {
+        self.output.clone()
+    }
+
+    fn transfer(&mut self) {
+        self.response = self.receive_byte(self.transfer_data);
+        self.transfer_control.enabled = false;
+        self.interrupt = true;
+    }
+
+    fn receive_byte(&mut self, value: u8) -> u8 {
+        let mut response = 0x00;
+
+        self.state = match self.state {
+            PacketState::Magic0 => {
+                if value == MAGIC_BYTES[0] {
+                    PacketState::Magic1
+                } else {
+                    PacketState::Magic0
+                }
+            },
+            PacketState::Magic1 => {
+                if value == MAGIC_BYTES[1] {
+                    PacketState::Command
+                } else if value == MAGIC_BYTES[0] {
+                    PacketState::Magic1
+                } else {
+                    PacketState::Magic0
+                }
+            },
+            PacketState::Command => {
+                self.command = value;
+                self.checksum = value as u16;
+                PacketState::Compression
+            },
+            PacketState::Compression => {
+                self.compression = value & 0x01 > 0;
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                PacketState::LengthLow
+            },
+            PacketState::LengthLow => {
+                self.length = value as u16;
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                PacketState::LengthHigh
+            },
+            PacketState::LengthHigh => {
+                self.length |= (value as u16) << 8;
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                self.packet_data.clear();
+                if self.length > 0 {
+                    PacketState::Data
+                } else {
+                    PacketState::ChecksumLow
+                }
+            },
+            PacketState::Data => {
+                self.packet_data.push(value);
+                self.checksum = self.checksum.wrapping_add(value as u16);
+                if self.packet_data.len() == self.length as usize {
+                    PacketState::ChecksumLow
+                } else {
+                    PacketState::Data
+                }
+            },
+            PacketState::ChecksumLow => {
+                self.received_checksum = value as u16;
+                PacketState::ChecksumHigh
+            },
+            PacketState::ChecksumHigh => {
+                self.received_checksum |= (value as u16) << 8;
+                PacketState::DeviceId
+            },
+            PacketState::DeviceId => {
+                response = DEVICE_ID;
+                self.execute_command();
+                PacketState::Status
+            },
+            PacketState::Status => {
+                response = self.status;
+                PacketState::Magic0
+            },
+        };
+
+        response
+    }
+
+    fn execute_command(&mut self) {
+        if self.checksum != self.received_checksum {
+            warn!(
+                "Printer packet checksum mismatch: expected {:04X}, got {:04X}",
+                self.checksum, self.received_checksum
+            );
+            self.status |= STATUS_CHECKSUM_ERROR;
+            return;
+        }
+        self.status &= !STATUS_CHECKSUM_ERROR;
+
+        match self.command {
+            COMMAND_INIT => {
+                debug!("Printer: initialize");
+                self.image_data.clear();
+                self.status = 0x00;
+                self.busy_inquiries = 0;
+            },
+            COMMAND_DATA => {
+                let data = if self.compression {
+                    decompress(&self.packet_data)
+                } else {
+                    self.packet_data.clone()
+                };
+                debug!("Printer: received {} bytes of image data", data.len());
+
+                let free = MAX_BANDS * BAND_BYTES - self.image_data.len();
+                self.image_data
+                    .extend_from_slice(&data[..data.len().min(free)]);
+
+                if !self.image_data.is_empty() {
+                    self.status |= STATUS_UNPROCESSED_DATA;
+                }
+                if self.image_data.len() == MAX_BANDS * BAND_BYTES {
+                    self.status |= STATUS_IMAGE_DATA_FULL;
+                }
+            },
+            COMMAND_PRINT => {
+                if self.packet_data.len() < 4 {
+                    warn!("Printer: print command without arguments");
+                    return;
+                }
+
+                let margins = self.packet_data[1];
+                let palette = self.packet_data[2];
+                debug!(
+                    "Printer: print with margins {:02X} and palette {:02X}",
+                    margins, palette
+                );
+
+                let image = self.render(margins, palette);
+                self.image_data.clear();
+                self.status &= !(STATUS_UNPROCESSED_DATA | STATUS_IMAGE_DATA_FULL);
+                self.busy_inquiries = BUSY_INQUIRIES;
+
+                self.output_image(image);
+            },
+            COMMAND_STATUS => {
+                if self.busy_inquiries > 0 {
+                    self.busy_inquiries -= 1;
+                    self.status |= STATUS_BUSY;
+                } else {
+                    self.status &= !STATUS_BUSY;
+                }
+            },
+            _ => {
+                debug!("Printer: unknown command {:02X}", self.command);
+            },
+        }
+    }
+
+    fn render(&self, margins: u8, palette: u8) -> PrinterImage {
+        let margin_before = (margins >> 4) as usize * MARGIN_LINES;
+        let margin_after = (margins & 0x0F) as usize * MARGIN_LINES;
+        // a trailing partial row of tiles is printed too, the rest of it stays blank
+        let tiles = self.image_data.len() / TILE_BYTES;
+        let image_lines = tiles.div_ceil(TILES_PER_ROW) * 8;
+        let height = margin_before + image_lines + margin_after;
+
+        let mut pixels = vec![Pixel::Color0; PRINTER_WIDTH * height];
+
+        for (tile_index, tile) in self.image_data.chunks_exact(TILE_BYTES).enumerate() {
+            let tile_x = (tile_index % TILES_PER_ROW) * 8;
+            let tile_y = margin_before + (tile_index / TILES_PER_ROW) * 8;
+
+            for row in 0..8 {
+                let low = tile[row * 2];
+                let high = tile[row * 2 + 1];
+
+                for col in 0..8 {
+                    let bit = 7 - col;
+                    let color = ((high >> bit) & 1) << 1 | ((low >> bit) & 1);
+                    let shade = (palette >> (color * 2)) & 0b11;
+
+                    pixels[(tile_y + row) * PRINTER_WIDTH + tile_x + col] = shade.into();
+                }
+            }
+        }
+
+        PrinterImage {
+            width: PRINTER_WIDTH,
+            height,
+            pixels,
+        }
+    }
+
+    fn output_image(&mut self, image: PrinterImage) {
+        let mut output = self.output.borrow_mut();
+
+        if let Some(output_dir) = &self.output_dir {
+            let path = output_dir.join(format!("print_{:03}.png", output.len()));
+            match image.write_png(&path) {
+                Ok(()) => info!("Printer: wrote image to {}", path.display()),
+                Err(err) => warn!("Printer: could not write {}: {}", path.display(), err),
+            }
+        }
+
+        output.push(image);
+    }
+}
+
+fn decompress(data: &[u8]) -> Vec<u8> {
+    let mut result = Vec::with_capacity(BAND_BYTES);
+    let mut i = 0;
+
+    while i < data.len() {
+        let control = data[i];
+        i += 1;
+
+        if control & 0x80 > 0 {
+            // run of a single byte
+            let length = (control & 0x7F) as usize + 2;
+            if let Some(value) = data.get(i) {
+                result.extend(std::iter::repeat_n(*value, length));
+            }
+            i += 1;
+        } else {
+            // literal bytes
+            let length = control as usize + 1;
+            let end = (i + length).min(data.len());
+            result.extend_from_slice(&data[i..end]);
+            i = end;
+        }
+    }
+
+    result
+}
+
+impl Serial for Printer {
+    fn read(&self) -> u8 {
+        self.response
+    }
+
+    fn write(&mut self, value: u8) {
+        self.transfer_data = value;
+    }
+
+    fn get_transfer_control(&self) -> u8 {
+        self.transfer_control.into()
+    }
+
+    fn set_transfer_control(&mut self, value: u8) {
+        self.transfer_control = value.into();
+        if self.transfer_control.enabled && self.transfer_control.clock_select {
+            self.transfer();
+        }
+    }
+
+    fn get_last_buffer(&self) -> &String {
+        &self.last_buffer
+    }
+
+    fn interrupt(&mut self) -> bool {
+        let result = self.interrupt;
+        self.interrupt = false;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send_packet(printer: &mut Printer, command: u8, compression: bool, data: &[u8]) -> [u8; 2] {
+        let mut packet = vec![
+            MAGIC_BYTES[0],
+            MAGIC_BYTES[1],
+            command,
+            compression as u8,
+            (data.len() & 0xFF) as u8,
+            (data.len() >> 8) as u8,
+        ];
+        packet.extend_from_slice(data);
+
+        let checksum = packet[2..]
+            .iter()
+            .fold(0u16, |acc, byte| acc.wrapping_add(*byte as u16));
+        packet.push((checksum & 0xFF) as u8);
+        packet.push((checksum >> 8) as u8);
+        packet.extend_from_slice(&[0x00, 0x00]);
+
+        let mut responses = vec![];
+        for byte in packet {
+            printer.write(byte);
+            printer.set_transfer_control(0x81);
+            assert_eq!(printer.get_transfer_control() & 0x80, 0x00);
+            responses.push(printer.read());
+        }
+
+        [
+            responses[responses.len() - 2],
+            responses[responses.len() - 1],
+        ]
+    }
+
+    #[test]
+    fn test_init_and_status() {
+        let mut printer = Printer::new();
+
+        assert_eq!(
+            send_packet(&mut printer, COMMAND_INIT, false, &[]),
+            [0x81, 0x00]
+        );
+        assert!(printer.interrupt());
+        assert!(!printer.interrupt());
+        assert_eq!(
+            send_packet(&mut printer, COMMAND_STATUS, false, &[]),
+            [0x81, 0x00]
+        );
+    }
+
+    #[test]
+    fn test_checksum_error() {
+        let mut printer = Printer::new();
+
+        for byte in [
+            0x88,
+            0x33,
+            COMMAND_INIT,
+            0x00,
+            0x00,
+            0x00,
+            0xFF,
+            0xFF,
+            0x00,
+            0x00,
+        ] {
+            printer.write(byte);
+            printer.set_transfer_control(0x81);
+        }
+
+        assert_eq!(printer.read(), STATUS_CHECKSUM_ERROR);
+    }
+
+    #[test]
+    fn test_print_band() {
+        let mut printer = Printer::new();
+        let output = printer.output();
+
+        // first tile uses all four colors in its first row, everything else is color 0
+        let mut band = vec![0x00; BAND_BYTES];
+        band[0] = 0b0101_0101;
+        band[1] = 0b0011_0011;
+
+        send_packet(&mut printer, COMMAND_INIT, false, &[]);
+        assert_eq!(
+            send_packet(&mut printer, COMMAND_DATA, false, &band),
+            [0x81, STATUS_UNPROCESSED_DATA]
+        );
+        send_packet(&mut printer, COMMAND_DATA, false, &[]);
+        send_packet(
+            &mut printer,
+            COMMAND_PRINT,
+            false,
+            &[0x01, 0x00, 0xE4, 0x40],
+        );
+
+        assert_eq!(
+            send_packet(&mut printer, COMMAND_STATUS, false, &[]),
+            [0x81, STATUS_BUSY]
+        );
+
+        let output = output.borrow();
+        assert_eq!(output.len(), 1);
+
+        let image = &output[0];
+        assert_eq!(image.width, PRINTER_WIDTH);
+        assert_eq!(image.height, 16);
+        assert_eq!(image.get_pixel(0, 0), Pixel::Color0);
+        assert_eq!(image.get_pixel(1, 0), Pixel::Color1);
+        assert_eq!(image.get_pixel(2, 0), Pixel::Color2);
+        assert_eq!(image.get_pixel(3, 0), Pixel::Color3);
+        assert_eq!(image.get_pixel(0, 1), Pixel::Color0);
+    }
+
+    #[test]
+    fn test_print_palette_and_margins() {
+        let mut printer = Printer::new();
+        let output = printer.output();
+
+        let band = vec![0x00; BAND_BYTES];
+
+        send_packet(&mut printer, COMMAND_INIT, false, &[]);
+        send_packet(&mut printer, COMMAND_DATA, false, &band);
+        // inverted palette, one margin unit before and two after
+        send_packet(
+            &mut printer,
+            COMMAND_PRINT,
+            false,
+            &[0x01, 0x12, 0x1B, 0x40],
+        );
+
+        let output = output.borrow();
+        let image = &output[0];
+        assert_eq!(image.height, 16 + 16 + 2 * 16);
+        assert_eq!(image.get_pixel(0, 15), Pixel::Color0);
+        assert_eq!(image.get_pixel(0, 16), Pixel::Color3);
+        assert_eq!(image.get_pixel(159, 31), Pixel::Color3);
+        assert_eq!(image.get_pixel(0, 32), Pixel::Color0);
+    }
+
+    #[test]
+    fn test_print_partial_row() {
+        let mut printer = Printer::new();
+        let output = printer.output();
+
+        // one full row of tiles and the first tile of the next one, which is fully dark
+        let mut data = vec![0x00; (TILES_PER_ROW + 1) * TILE_BYTES];
+        data[TILES_PER_ROW * TILE_BYTES..].fill(0xFF);
+
+        send_packet(&mut printer, COMMAND_INIT, false, &[]);
+        send_packet(&mut printer, COMMAND_DATA, false, &data);
+        send_packet(
+            &mut printer,
+            COMMAND_PRINT,
+            false,
+            &[0x01, 0x00, 0xE4, 0x40],
+        );
+
+        let output = output.borrow();
+        let image = &output[0];
+        assert_eq!(image.height, 16);
+        assert_eq!(image.get_pixel(0, 7), Pixel::Color0);
+        assert_eq!(image.get_pixel(7, 15), Pixel::Color3);
+        assert_eq!(image.get_pixel(8, 8), Pixel::Color0);
+    }
+
+    #[test]
+    fn test_decompress() {
+        assert_eq!(
+            decompress(&[0x81, 0xAA, 0x02, 0x01, 0x02, 0x03, 0x80, 0xFF]),
+            vec![0xAA, 0xAA, 0xAA, 0x01, 0x02, 0x03, 0xFF, 0xFF]
+        );
+    }
+}
@@ -1,13 +1,18 @@
 use tracing::{debug, trace};
 
-use crate::cpu::interrupts::InterruptFlags;
-use crate::graphics::Ppu;
+use crate::cpu::bus::Bus;
+use crate::cpu::interrupts::{Interrupt, InterruptFlags};
+use crate::emulator::ExecutionError;
+use crate::graphics::{PPU_STEPS_PER_CYCLE, Ppu};
 use crate::joypad::JoypadRegister;
 use crate::memory::mbc::Mbc;
 use crate::memory::{
-    E_RAM_BANK_ADDR, ECHO_RAM_ADDR, H_RAM_ADDR, H_RAM_SIZE, IE_REGISTER_ADDR, IO_REGISTERS_ADDR,
-    OAM_ADDR, TILE_MAPS_ADDR, UNUSABLE_ADDR, V_RAM_ADDR, W_RAM_BANK_0_ADDR, W_RAM_BANK_SIZE,
+    CGB_FLAG_ADDR, E_RAM_BANK_ADDR, ECHO_RAM_ADDR, H_RAM_ADDR, H_RAM_SIZE, IE_REGISTER_ADDR,
+    IO_REGISTERS_ADDR, OAM_ADDR, TILE_MAPS_ADDR, UNUSABLE_ADDR, V_RAM_ADDR, W_RAM_BANK_0_ADDR,
+    W_RAM_BANK_SIZE, W_RAM_BANK_X_ADDR,
 };
+use crate::model::BootRom;
+use crate::model::Model;
 use crate::serial::Serial;
 use crate::timer::TimerRegisters;
 
@@ -22,25 +27,33 @@ pub struct IoRegisters {
 }
 
 impl IoRegisters {
-    pub fn new(serial: Box<dyn Serial>) -> Self {
+    pub fn new(serial: Box<dyn Serial>, system_counter: u16) -> Self {
         IoRegisters {
             joypad: JoypadRegister::default(),
             serial,
             interrupt_flags: 0.into(),
             interrupt_enable: 0,
-            timer: TimerRegisters::default(),
+            timer: TimerRegisters::new(system_counter),
         }
     }
 }
 
 pub struct System {
+    pub graphics_enabled: bool,
+
     pub oam_transfer: bool,
     oam_transfer_source: u16,
     oam_transfer_cycle: u16,
 
+    model: Model,
+    cgb_mode: bool,
+    boot_rom: Option<BootRom>,
+
     mbc: Box<dyn Mbc + 'static>,
     w_ram: Vec<u8>,
+    w_ram_bank: u8,
     h_ram: [u8; H_RAM_SIZE],
+    v_ram_bank: u8,
 
     pub io: IoRegisters,
     pub graphics: Ppu,
@@ -48,20 +61,85 @@ pub struct System {
 
 impl System {
     pub fn new(mbc: Box<dyn Mbc + 'static>, serial: Box<dyn Serial>) -> Self {
+        Self::new_with_model(mbc, serial, Model::default(), None)
+    }
+
+    pub fn new_with_model(
+        mbc: Box<dyn Mbc + 'static>,
+        serial: Box<dyn Serial>,
+        model: Model,
+        boot_rom: Option<BootRom>,
+    ) -> Self {
+        // the boot ROM starts with the system counter at zero
+        let system_counter = if boot_rom.is_some() {
+            0x0000
+        } else {
+            model.post_boot_system_counter()
+        };
+
+        // cgb features are only available if the cartridge supports them
+        let cgb_mode = model.is_cgb() && (mbc.read_rom(CGB_FLAG_ADDR) & 0x80) > 0;
+
         System {
+            graphics_enabled: true,
+
             oam_transfer: false,
             oam_transfer_source: 0x00,
             oam_transfer_cycle: 0,
 
+            model,
+            cgb_mode,
+            boot_rom,
+
             mbc,
-            w_ram: vec![0; W_RAM_BANK_SIZE * 2],
+            w_ram: model.initial_w_ram(W_RAM_BANK_SIZE * model.w_ram_banks()),
+            w_ram_bank: 1,
             h_ram: [0; H_RAM_SIZE],
+            v_ram_bank: 0,
 
-            io: IoRegisters::new(serial),
+            io: IoRegisters::new(serial, system_counter),
             graphics: Ppu::default(),
         }
     }
 
+    pub fn model(&self) -> Model {
+        self.model
+    }
+
+    pub fn cgb_mode(&self) -> bool {
+        self.cgb_mode
+    }
+
+    pub fn boot_rom_mapped(&self) -> bool {
+        self.boot_rom.is_some()
+    }
+
+    pub fn rom_bank(&self, address: u16) -> usize {
+        self.mbc.rom_bank(address)
+    }
+
+    pub fn ram_bank(&self) -> usize {
+        self.mbc.ram_bank()
+    }
+
+    // WRAM bank mapped at 0xD000, always 1 outside of CGB mode
+    pub fn w_ram_bank(&self) -> usize {
+        self.w_ram_bank as usize
+    }
+
+    // VRAM bank mapped at 0x8000, always 0 outside of CGB mode
+    pub fn v_ram_bank(&self) -> usize {
+        self.v_ram_bank as usize
+    }
+
+    fn w_ram_index(&self, address: u16) -> usize {
+        if address < W_RAM_BANK_X_ADDR {
+            (address - W_RAM_BANK_0_ADDR) as usize
+        } else {
+            (self.w_ram_bank as usize * W_RAM_BANK_SIZE) + (address - W_RAM_BANK_X_ADDR) as usize
+        }
+    }
+
     fn oam_transfer(&mut self, address: u8) {
         self.oam_transfer = true;
         self.oam_transfer_source = address as u16 * 0x0100;
@@ -107,6 +185,11 @@ impl System {
             0xFF49 => self.graphics.registers.get_obj_palette(1),
             0xFF4A => self.graphics.registers.get_window_y(),
             0xFF4B => self.graphics.registers.get_window_x(),
+
+            // cgb
+            0xFF4F if self.cgb_mode => 0b1111_1110 | self.v_ram_bank,
+            0xFF70 if self.cgb_mode => 0b1111_1000 | self.w_ram_bank,
+
             _ => {
                 debug!("Reading from unimplemented i/o register 0x{:02X}", address);
                 0xFF
@@ -132,6 +215,14 @@ impl System {
             // interrupt
             0xFF0F => self.io.interrupt_flags = value.into(),
 
+            // boot rom
+            0xFF50 => {
+                if value != 0 && self.boot_rom.is_some() {
+                    debug!("Unmapping boot ROM");
+                    self.boot_rom = None;
+                }
+            },
+
             // TODO: audio
             0xFF10..=0xFF26 => {
                 debug!(
@@ -153,6 +244,11 @@ impl System {
             0xFF49 => self.graphics.registers.set_obj_palette(1, value),
             0xFF4A => self.graphics.registers.set_window_y(value),
             0xFF4B => self.graphics.registers.set_window_x(value),
+
+            // cgb
+            0xFF4F if self.cgb_mode => self.v_ram_bank = value & 0b1,
+            0xFF70 if self.cgb_mode => self.w_ram_bank = (value & 0b111).max(1),
+
             _ => {
                 debug!("Writing to unimplemented i/o register 0x{:02X}", address);
             },
@@ -162,25 +258,35 @@ impl System {
 
     fn read_byte_internal(&self, address: u16) -> u8 {
         match address {
-            0x0000..V_RAM_ADDR => self.mbc.read_rom(address),
-            V_RAM_ADDR..TILE_MAPS_ADDR => self.graphics.tile_data.get_byte(address - V_RAM_ADDR),
+            0x0000..V_RAM_ADDR => {
+                if let Some(value) = self.boot_rom.as_ref().and_then(|b| b.read(address)) {
+                    value
+                } else {
+                    self.mbc.read_rom(address)
+                }
+            },
+            V_RAM_ADDR..TILE_MAPS_ADDR => self
+                .graphics
+                .tile_data(self.v_ram_bank())
+                .get_byte(address - V_RAM_ADDR),
             TILE_MAPS_ADDR..E_RAM_BANK_ADDR => {
+                let tile_maps = self.graphics.tile_maps(self.v_ram_bank());
                 let rel_addr = address - TILE_MAPS_ADDR;
                 if rel_addr < 32 * 32 {
-                    self.graphics.tile_maps[0].get_byte(rel_addr)
+                    tile_maps[0].get_byte(rel_addr)
                 } else {
-                    self.graphics.tile_maps[1].get_byte(rel_addr - (32 * 32))
+                    tile_maps[1].get_byte(rel_addr - (32 * 32))
                 }
             },
             E_RAM_BANK_ADDR..W_RAM_BANK_0_ADDR => self.mbc.read_ram(address - E_RAM_BANK_ADDR),
-            W_RAM_BANK_0_ADDR..ECHO_RAM_ADDR => self.w_ram[(address - W_RAM_BANK_0_ADDR) as usize],
+            W_RAM_BANK_0_ADDR..ECHO_RAM_ADDR => self.w_ram[self.w_ram_index(address)],
             ECHO_RAM_ADDR..OAM_ADDR => self.read_byte(address - ECHO_RAM_ADDR + W_RAM_BANK_0_ADDR),
             OAM_ADDR..UNUSABLE_ADDR => self.graphics.read_oam_byte(address - OAM_ADDR),
             UNUSABLE_ADDR..IO_REGISTERS_ADDR => {
                 if self.oam_transfer {
                     0xFF
                 } else {
-                    0x00
+                    self.model.unusable_memory_read(address)
                 }
             },
             IO_REGISTERS_ADDR..H_RAM_ADDR => self.get_io_register(address),
@@ -226,21 +332,23 @@ impl System {
             0x0000..V_RAM_ADDR => self.mbc.write_rom(address, value),
             V_RAM_ADDR..TILE_MAPS_ADDR => self
                 .graphics
-                .tile_data
+                .tile_data_mut(self.v_ram_bank as usize)
                 .set_byte(address - V_RAM_ADDR, value),
             TILE_MAPS_ADDR..E_RAM_BANK_ADDR => {
+                let tile_maps = self.graphics.tile_maps_mut(self.v_ram_bank as usize);
                 let rel_addr = address - TILE_MAPS_ADDR;
                 if rel_addr < 32 * 32 {
-                    self.graphics.tile_maps[0].set_byte(rel_addr, value)
+                    tile_maps[0].set_byte(rel_addr, value)
                 } else {
-                    self.graphics.tile_maps[1].set_byte(rel_addr - (32 * 32), value)
+                    tile_maps[1].set_byte(rel_addr - (32 * 32), value)
                 }
             },
             E_RAM_BANK_ADDR..W_RAM_BANK_0_ADDR => {
                 self.mbc.write_ram(address - E_RAM_BANK_ADDR, value)
             },
             W_RAM_BANK_0_ADDR..ECHO_RAM_ADDR => {
-                self.w_ram[(address - W_RAM_BANK_0_ADDR) as usize] = value
+                let index = self.w_ram_index(address);
+                self.w_ram[index] = value
             },
             ECHO_RAM_ADDR..OAM_ADDR => {
                 self.write_byte(address - ECHO_RAM_ADDR + W_RAM_BANK_0_ADDR, value)
@@ -275,3 +383,58 @@ impl System {
         self.oam_transfer_cycle += 1;
     }
 }
+
+impl Bus for System {
+    fn read_byte(&mut self, address: u16) -> u8 {
+        System::read_byte(self, address)
+    }
+
+    fn peek_byte(&self, address: u16) -> u8 {
+        System::read_byte(self, address)
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        System::write_byte(self, address, value)
+    }
+
+    fn interrupt_enable(&self) -> u8 {
+        self.io.interrupt_enable
+    }
+
+    fn interrupt_flags(&self) -> u8 {
+        self.io.interrupt_flags.into()
+    }
+
+    fn set_interrupt_flags(&mut self, value: u8) {
+        self.io.interrupt_flags = value.into();
+    }
+
+    fn bank(&self, address: u16) -> Option<usize> {
+        (address < V_RAM_ADDR).then(|| self.rom_bank(address))
+    }
+
+    fn tick(&mut self) -> Result<(), ExecutionError> {
+        let mut v_blank_interrupt = false;
+        if self.graphics_enabled {
+            for _ in 0..PPU_STEPS_PER_CYCLE {
+                v_blank_interrupt |= self.graphics.step();
+            }
+        }
+
+        let timer_interrupt = self.io.timer.step()?;
+        self.io.serial.step();
+        let joypad_interrupt = self.io.joypad.interrupt();
+
+        if v_blank_interrupt {
+            self.io.interrupt_flags.request(Interrupt::VBlank);
+        } else if timer_interrupt {
+            self.io.interrupt_flags.request(Interrupt::Timer);
+        } else if joypad_interrupt {
+            self.io.interrupt_flags.request(Interrupt::Joypad);
+        } else if self.io.serial.interrupt() {
+            self.io.interrupt_flags.request(Interrupt::Serial);
+        }
+
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@ use tracing_subscriber::Layer;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::registry::LookupSpan;
 
+use crate::emulator::Emulator;
+
 pub fn logging_layer_stdout<S>() -> Box<dyn Layer<S> + Send + Sync>
 where
     S: tracing_core::Subscriber,
@@ -26,3 +28,16 @@ pub fn setup_default_logger() -> dispatcher::DefaultGuard {
 
     guard
 }
+
+// Emulator for a ROM of `banks` 16 KiB banks with the code placed at the given ROM offsets, more
+// than two banks use MBC1. The boot ROM is skipped.
+pub fn test_emulator(banks: usize, code: &[(usize, &[u8])]) -> Emulator {
+    let mut rom = vec![0; banks * 0x4000];
+    rom[0x0147] = if banks > 2 { 0x01 } else { 0x00 };
+    rom[0x0148] = (banks / 2).trailing_zeros() as u8;
+    for (address, bytes) in code {
+        rom[*address..*address + bytes.len()].copy_from_slice(bytes);
+    }
+
+    Emulator::new_from_buffer(rom, true, None, None).unwrap()
+}
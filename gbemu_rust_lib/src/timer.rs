@@ -2,6 +2,7 @@ use std::fmt::Debug;
 use tracing::{debug, instrument};
 
 use crate::emulator::ExecutionError;
+use crate::model::Model;
 
 const TAC_ENABLE_BIT: usize = 2;
 const TAC_CYCLES_256_BIT: usize = 9;
@@ -40,8 +41,14 @@ pub struct TimerRegisters {
 
 impl Default for TimerRegisters {
     fn default() -> Self {
+        Self::new(Model::default().post_boot_system_counter())
+    }
+}
+
+impl TimerRegisters {
+    pub fn new(system_counter: u16) -> Self {
         Self {
-            system_counter: 0xAB00,
+            system_counter,
             counter: 0x00,
             modulo: 0x00,
             control: 0xF8,
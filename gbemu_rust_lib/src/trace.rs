@@ -0,0 +1,405 @@
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use tracing::{info, warn};
+
+use crate::cpu::Cpu;
+use crate::cpu::bus::Bus;
+use crate::cpu::registers::Registers;
+use crate::disassembler::{Symbols, disassemble};
+use crate::memory::MemoryRegion;
+
+// bytes at the PC stored per instruction, enough for the longest instruction
+const TRACE_BYTES: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceFormat {
+    // the format of https://github.com/robert/gameboy-doctor, same as the `cpu::state` event
+    GameboyDoctor,
+    // bank, address and disassembly followed by the registers, similar to BGB's trace log
+    Bgb,
+    JsonLines,
+}
+
+impl TraceFormat {
+    pub const ALL: [TraceFormat; 3] = [Self::GameboyDoctor, Self::Bgb, Self::JsonLines];
+}
+
+impl Display for TraceFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::GameboyDoctor => "doctor",
+            Self::Bgb => "bgb",
+            Self::JsonLines => "json",
+        })
+    }
+}
+
+impl FromStr for TraceFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown trace format '{}'", s))
+    }
+}
+
+// State of the CPU right before the instruction at `pc` executed
+#[derive(Debug, Clone)]
+pub struct TraceEntry {
+    pub cycle: u64,
+    pub pc: u16,
+    pub bank: Option<usize>,
+    pub bytes: [u8; TRACE_BYTES],
+    pub registers: Registers,
+}
+
+// Serves the recorded bytes to the disassembler
+struct EntryBus<'a>(&'a TraceEntry);
+
+impl Bus for EntryBus<'_> {
+    fn read_byte(&mut self, address: u16) -> u8 {
+        self.peek_byte(address)
+    }
+
+    fn write_byte(&mut self, _address: u16, _value: u8) {}
+
+    fn peek_byte(&self, address: u16) -> u8 {
+        let offset = address.wrapping_sub(self.0.pc) as usize;
+        self.0.bytes.get(offset).copied().unwrap_or(0xFF)
+    }
+
+    fn interrupt_enable(&self) -> u8 {
+        0
+    }
+
+    fn interrupt_flags(&self) -> u8 {
+        0
+    }
+
+    fn set_interrupt_flags(&mut self, _value: u8) {}
+
+    fn bank(&self, _address: u16) -> Option<usize> {
+        self.0.bank
+    }
+}
+
+impl TraceEntry {
+    fn flags(&self) -> String {
+        "ZNHC"
+            .chars()
+            .enumerate()
+            .map(|(index, flag)| {
+                if self.registers.f & (0x80 >> index) != 0 {
+                    flag
+                } else {
+                    '-'
+                }
+            })
+            .collect()
+    }
+
+    fn location(&self) -> String {
+        match self.bank {
+            Some(bank) => format!("ROM{:X}:{:04X}", bank, self.pc),
+            None => format!(
+                "{}:{:04X}",
+                MemoryRegion::from_address(self.pc).name(),
+                self.pc
+            ),
+        }
+    }
+
+    pub fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        format: TraceFormat,
+        symbols: &Symbols,
+    ) -> std::io::Result<()> {
+        let r = &self.registers;
+
+        match format {
+            TraceFormat::GameboyDoctor => writeln!(
+                writer,
+                "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+                r.a,
+                r.f,
+                r.b,
+                r.c,
+                r.d,
+                r.e,
+                r.h,
+                r.l,
+                r.sp,
+                self.pc,
+                self.bytes[0],
+                self.bytes[1],
+                self.bytes[2],
+                self.bytes[3],
+            ),
+            TraceFormat::Bgb => {
+                let instruction = disassemble(&EntryBus(self), self.pc, symbols);
+                let bytes = instruction
+                    .bytes
+                    .iter()
+                    .map(|byte| format!("{:02X}", byte))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+
+                writeln!(
+                    writer,
+                    "{} {:<8} {:<24} A:{:02X} F:{} BC:{:02X}{:02X} DE:{:02X}{:02X} HL:{:02X}{:02X} SP:{:04X} cy:{}",
+                    self.location(),
+                    bytes,
+                    instruction.text,
+                    r.a,
+                    self.flags(),
+                    r.b,
+                    r.c,
+                    r.d,
+                    r.e,
+                    r.h,
+                    r.l,
+                    r.sp,
+                    self.cycle,
+                )
+            },
+            TraceFormat::JsonLines => {
+                let bank = match self.bank {
+                    Some(bank) => bank.to_string(),
+                    None => "null".to_owned(),
+                };
+                let bytes = self.bytes.map(|byte| byte.to_string()).join(",");
+
+                writeln!(
+                    writer,
+                    "{{\"cycle\":{},\"pc\":{},\"bank\":{},\"bytes\":[{}],\"a\":{},\"f\":{},\"b\":{},\"c\":{},\"d\":{},\"e\":{},\"h\":{},\"l\":{},\"sp\":{}}}",
+                    self.cycle, self.pc, bank, bytes, r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l, r.sp,
+                )
+            },
+        }
+    }
+}
+
+// Ring buffer of the last executed instructions, disabled until a capacity is set
+#[derive(Default)]
+pub struct TraceRecorder {
+    capacity: usize,
+    entries: VecDeque<TraceEntry>,
+    // M-cycles since the emulator started
+    cycles: u64,
+    // written whenever the emulator stops at a breakpoint, watchpoint or with an error
+    dump_on_stop: Option<(PathBuf, TraceFormat)>,
+}
+
+impl TraceRecorder {
+    pub fn enable(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        while self.entries.len() > capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub fn disable(&mut self) {
+        self.enable(0);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &TraceEntry> + ExactSizeIterator {
+        self.entries.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn set_dump_on_stop(&mut self, dump: Option<(PathBuf, TraceFormat)>) {
+        self.dump_on_stop = dump;
+    }
+
+    // Called once per M-cycle, records the next instruction once the previous one completed
+    pub(crate) fn tick<B: Bus>(&mut self, cpu: &Cpu, bus: &B, completed: bool) {
+        self.cycles += 1;
+
+        if !self.is_enabled() || !completed || cpu.halted {
+            return;
+        }
+        let Some(pc) = cpu.instruction_address() else {
+            return;
+        };
+
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(TraceEntry {
+            cycle: self.cycles,
+            pc,
+            bank: bus.bank(pc),
+            bytes: std::array::from_fn(|offset| bus.peek_byte(pc.wrapping_add(offset as u16))),
+            registers: cpu.registers.clone(),
+        });
+    }
+
+    pub fn export<W: Write>(
+        &self,
+        writer: &mut W,
+        format: TraceFormat,
+        symbols: &Symbols,
+    ) -> std::io::Result<()> {
+        for entry in &self.entries {
+            entry.write(writer, format, symbols)?;
+        }
+        writer.flush()
+    }
+
+    pub fn save(&self, path: &Path, format: TraceFormat, symbols: &Symbols) -> std::io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.export(&mut writer, format, symbols)
+    }
+
+    pub(crate) fn dump(&self, symbols: &Symbols) {
+        let Some((path, format)) = &self.dump_on_stop else {
+            return;
+        };
+
+        match self.save(path, *format, symbols) {
+            Ok(()) => info!(
+                "Wrote the last {} instructions to {}",
+                self.entries.len(),
+                path.display()
+            ),
+            Err(err) => warn!("Could not write trace to {}: {}", path.display(), err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::debugger::{Breakpoint, RunTarget, StopReason};
+    use crate::emulator::Emulator;
+    use crate::tests::{setup_default_logger, test_emulator};
+
+    use super::*;
+
+    const LOOP: [u8; 5] = [
+        0x3C, // INC A
+        0x06, 0x42, // LD B, 0x42
+        0x18, 0xFB, // JR -5
+    ];
+
+    fn export(emu: &Emulator, format: TraceFormat) -> Vec<String> {
+        let mut buffer = vec![];
+        emu.trace
+            .export(&mut buffer, format, &Symbols::new())
+            .unwrap();
+
+        String::from_utf8(buffer)
+            .unwrap()
+            .lines()
+            .map(str::to_owned)
+            .collect()
+    }
+
+    #[test]
+    fn test_ring_buffer() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(2, &[(0x0100, &LOOP)]);
+        for _ in 0..100 {
+            emu.step().unwrap();
+        }
+        assert_eq!(emu.trace.entries().count(), 0);
+        assert_eq!(emu.trace.cycles(), 100);
+
+        emu.trace.enable(4);
+        for _ in 0..100 {
+            emu.step().unwrap();
+        }
+
+        let entries: Vec<_> = emu.trace.entries().collect();
+        assert_eq!(entries.len(), 4);
+        assert!(entries.windows(2).all(|pair| pair[0].cycle < pair[1].cycle));
+        assert!(
+            entries
+                .iter()
+                .all(|entry| [0x0100, 0x0101, 0x0103].contains(&entry.pc))
+        );
+
+        emu.trace.enable(2);
+        assert_eq!(emu.trace.entries().count(), 2);
+    }
+
+    #[test]
+    fn test_export() {
+        let _guard = setup_default_logger();
+
+        let mut emu = test_emulator(2, &[(0x0100, &LOOP)]);
+        emu.trace.enable(8);
+        // INC A takes one cycle, then the state before LD B, 0x42 is recorded
+        emu.step().unwrap();
+
+        assert_eq!(
+            export(&emu, TraceFormat::GameboyDoctor),
+            ["A:02 F:00 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0101 PCMEM:06,42,18,FB",]
+        );
+        assert_eq!(
+            export(&emu, TraceFormat::Bgb),
+            [
+                "ROM0:0101 06 42    ld b, $42                A:02 F:---- BC:0013 DE:00D8 HL:014D SP:FFFE cy:1",
+            ]
+        );
+        assert_eq!(
+            export(&emu, TraceFormat::JsonLines),
+            [
+                "{\"cycle\":1,\"pc\":257,\"bank\":0,\"bytes\":[6,66,24,251],\"a\":2,\"f\":0,\"b\":0,\"c\":19,\"d\":0,\"e\":216,\"h\":1,\"l\":77,\"sp\":65534}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dump_on_breakpoint() {
+        let _guard = setup_default_logger();
+
+        let path = std::env::temp_dir().join(format!("trace_{}.log", std::process::id()));
+        let mut emu = test_emulator(2, &[(0x0100, &LOOP)]);
+        emu.trace.enable(16);
+        emu.trace
+            .set_dump_on_stop(Some((path.clone(), TraceFormat::GameboyDoctor)));
+        emu.debugger.add_breakpoint(Breakpoint::new(0x0103));
+
+        let reason = emu.run_until(RunTarget::Breakpoint, 1000).unwrap();
+        assert!(matches!(reason, StopReason::Breakpoint { .. }));
+
+        let trace = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(trace.lines().count(), 2);
+        assert!(trace.lines().last().unwrap().contains("PC:0103"));
+    }
+
+    #[test]
+    fn test_trace_format() {
+        let _guard = setup_default_logger();
+
+        for format in TraceFormat::ALL {
+            assert_eq!(format.to_string().parse(), Ok(format));
+        }
+        assert!("bgb2".parse::<TraceFormat>().is_err());
+    }
+}
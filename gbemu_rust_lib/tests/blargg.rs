@@ -0,0 +1,306 @@
+mod helpers;
+
+use helpers::blargg::{BlarggResult, run_blargg, test_blargg};
+use helpers::setup_default_logger;
+
+fn memory_signature_rom(status: u8) -> Vec<u8> {
+    let instructions = [
+        0x3E, 0x0A, // LD A, 0x0A
+        0xEA, 0x00, 0x00, // LD (0x0000), A
+        0x21, 0x00, 0xA0, // LD HL, 0xA000
+        0x3E, 0x80, // LD A, 0x80
+        0x22, // LD (HL+), A
+        0x3E, 0xDE, // LD A, 0xDE
+        0x22, // LD (HL+), A
+        0x3E, 0xB0, // LD A, 0xB0
+        0x22, // LD (HL+), A
+        0x3E, 0x61, // LD A, 0x61
+        0x22, // LD (HL+), A
+        0x3E, b'o', // LD A, 'o'
+        0x22, // LD (HL+), A
+        0x3E, b'k', // LD A, 'k'
+        0x22, // LD (HL+), A
+        0x3E, 0x00, // LD A, 0x00
+        0x22, // LD (HL+), A
+        0x3E, status, // LD A, status
+        0xEA, 0x00, 0xA0, // LD (0xA000), A
+        0x18, 0xFE, // JR -2
+    ];
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+    // MBC1 with 8 KiB RAM
+    rom_buffer[0x0147] = 0x02;
+    rom_buffer[0x0149] = 0x02;
+
+    rom_buffer
+}
+
+#[test]
+fn test_memory_signature() {
+    let _guard = setup_default_logger();
+
+    assert_eq!(
+        run_blargg(memory_signature_rom(0x00), 100_000),
+        BlarggResult::Passed
+    );
+    assert_eq!(
+        run_blargg(memory_signature_rom(0x03), 100_000),
+        BlarggResult::Failed {
+            code: 3,
+            output: "ok".to_owned()
+        }
+    );
+    assert_eq!(
+        run_blargg(memory_signature_rom(0x80), 100_000),
+        BlarggResult::Timeout {
+            output: "ok".to_owned()
+        }
+    );
+}
+
+#[test]
+fn test_instr_timing() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/instr_timing/instr_timing.gb");
+}
+
+#[test]
+fn test_mem_timing() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing/mem_timing.gb");
+}
+
+#[test]
+fn test_mem_timing_01() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing/individual/01-read_timing.gb");
+}
+
+#[test]
+fn test_mem_timing_02() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing/individual/02-write_timing.gb");
+}
+
+#[test]
+fn test_mem_timing_03() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing/individual/03-modify_timing.gb");
+}
+
+#[test]
+fn test_mem_timing_2() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing-2/mem_timing.gb");
+}
+
+#[test]
+fn test_mem_timing_2_01() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing-2/rom_singles/01-read_timing.gb");
+}
+
+#[test]
+fn test_mem_timing_2_02() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing-2/rom_singles/02-write_timing.gb");
+}
+
+#[test]
+fn test_mem_timing_2_03() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/mem_timing-2/rom_singles/03-modify_timing.gb");
+}
+
+#[test]
+#[ignore = "the HALT bug is not emulated yet"]
+fn test_halt_bug() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/halt_bug.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/dmg_sound.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_01() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/01-registers.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_02() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/02-len ctr.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_03() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/03-trigger.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_04() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/04-sweep.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_05() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/05-sweep details.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_06() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/06-overflow on trigger.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_07() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/07-len sweep period sync.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_08() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/08-len ctr during power.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_09() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/09-wave read while on.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_10() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/10-wave trigger while on.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_11() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/11-regs after power.gb");
+}
+
+#[test]
+#[ignore = "audio is not emulated yet"]
+fn test_dmg_sound_12() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/dmg_sound/rom_singles/12-wave write while on.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/oam_bug.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_1() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/1-lcd_sync.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_2() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/2-causes.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_3() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/3-non_causes.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_4() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/4-scanline_timing.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_5() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/5-timing_bug.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_6() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/6-timing_no_bug.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_7() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/7-timing_effect.gb");
+}
+
+#[test]
+#[ignore = "OAM corruption is not emulated yet"]
+fn test_oam_bug_8() {
+    let _guard = setup_default_logger();
+
+    test_blargg("../external/test_roms/blargg/oam_bug/rom_singles/8-instr_effect.gb");
+}
@@ -0,0 +1,91 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::setup_default_logger;
+
+fn boot_rom() -> Vec<u8> {
+    let mut boot_rom = vec![0x00; 0x0100];
+    boot_rom[0x00FC..].copy_from_slice(&[
+        0x3E, 0x01, // LD A, 0x01
+        0xE0, 0x50, // LDH (0x50), A
+    ]);
+
+    boot_rom
+}
+
+fn cartridge_rom() -> Vec<u8> {
+    let instructions = [
+        0x06, 0x42, // LD B, 0x42
+        0x18, 0xFE, // JR -2
+    ];
+    let mut rom_buffer = vec![0x00; 32 * 1024];
+    rom_buffer[0x0000] = 0x76;
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+
+    rom_buffer
+}
+
+#[test]
+fn test_boot_rom_execution() {
+    let _guard = setup_default_logger();
+
+    let mut emu = Emulator::new_from_buffer_with_model(
+        cartridge_rom(),
+        Model::Dmg,
+        Some(boot_rom()),
+        false,
+        None,
+        None,
+    )
+    .unwrap();
+
+    assert_eq!(emu.system.read_byte(0x0000), 0x00);
+    assert_eq!(emu.system.read_byte(0x00FC), 0x3E);
+    assert_eq!(emu.system.read_byte(0x0100), 0x06);
+    assert_eq!(emu.system.io.timer.divider(), 0x00);
+
+    for _ in 0..0x0200 {
+        emu.step().unwrap();
+    }
+
+    assert!(!emu.system.boot_rom_mapped());
+    assert_eq!(emu.system.read_byte(0x0000), 0x76);
+    assert_eq!(emu.cpu.registers.b, 0x42);
+    assert!((0x0100..0x0105).contains(&emu.cpu.registers.pc));
+}
+
+#[test]
+fn test_post_boot_state_without_boot_rom() {
+    let _guard = setup_default_logger();
+
+    for model in Model::ALL {
+        let emu =
+            Emulator::new_from_buffer_with_model(cartridge_rom(), model, None, false, None, None)
+                .unwrap();
+
+        let expected = model.post_boot_registers(0x00);
+        assert_eq!(emu.cpu.registers.a, expected.a);
+        assert_eq!(emu.cpu.registers.get_hl(), expected.get_hl());
+        assert_eq!(
+            emu.system.io.timer.divider(),
+            (model.post_boot_system_counter() >> 8) as u8
+        );
+    }
+}
+
+#[test]
+fn test_invalid_boot_rom_size() {
+    let _guard = setup_default_logger();
+
+    assert!(
+        Emulator::new_from_buffer_with_model(
+            cartridge_rom(),
+            Model::Cgb,
+            Some(boot_rom()),
+            false,
+            None,
+            None,
+        )
+        .is_err()
+    );
+}
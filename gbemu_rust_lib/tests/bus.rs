@@ -0,0 +1,93 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::setup_default_logger;
+
+// instrumentation wrapper which counts all bus accesses of the CPU
+struct CountingBus<'a> {
+    system: &'a mut System,
+    reads: usize,
+    writes: usize,
+    ticks: usize,
+}
+
+impl Bus for CountingBus<'_> {
+    fn read_byte(&mut self, address: u16) -> u8 {
+        self.reads += 1;
+        self.system.read_byte(address)
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        self.writes += 1;
+        self.system.write_byte(address, value);
+    }
+
+    fn peek_byte(&self, address: u16) -> u8 {
+        self.system.peek_byte(address)
+    }
+
+    fn interrupt_enable(&self) -> u8 {
+        self.system.interrupt_enable()
+    }
+
+    fn interrupt_flags(&self) -> u8 {
+        self.system.interrupt_flags()
+    }
+
+    fn set_interrupt_flags(&mut self, value: u8) {
+        self.system.set_interrupt_flags(value);
+    }
+
+    fn tick(&mut self) -> Result<(), ExecutionError> {
+        self.ticks += 1;
+        self.system.tick()
+    }
+}
+
+fn counter_rom() -> Vec<u8> {
+    let instructions = [
+        0x21, 0x00, 0xC0, // LD HL, 0xC000
+        0x34, // INC (HL)
+        0x18, 0xFD, // JR -3
+    ];
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+
+    rom_buffer
+}
+
+#[test]
+fn test_instrumented_bus() {
+    let _guard = setup_default_logger();
+
+    let mut emu = Emulator::new_from_buffer(counter_rom(), true, None, None).unwrap();
+    let mut reference = Emulator::new_from_buffer(counter_rom(), true, None, None).unwrap();
+
+    let initial = emu.system.read_byte(0xC000);
+
+    let mut bus = CountingBus {
+        system: &mut emu.system,
+        reads: 0,
+        writes: 0,
+        ticks: 0,
+    };
+    // LD HL, n16 and 10 iterations of INC (HL) and JR
+    let cycles = 3 + 10 * (3 + 3);
+    for _ in 0..cycles {
+        emu.cpu.cycle(&mut bus).unwrap();
+        reference.step().unwrap();
+    }
+
+    assert_eq!(bus.ticks, cycles);
+    // opcode fetches, immediates and INC (HL) reads
+    assert_eq!(bus.reads, 3 + 10 * (1 + 1 + 2));
+    assert_eq!(bus.writes, 10);
+
+    assert_eq!(emu.system.read_byte(0xC000), initial.wrapping_add(10));
+    assert_eq!(reference.system.read_byte(0xC000), initial.wrapping_add(10));
+    assert_eq!(format!("{:?}", emu.cpu), format!("{:?}", reference.cpu));
+    assert_eq!(
+        emu.system.graphics.registers.get_lcd_ly(),
+        reference.system.graphics.registers.get_lcd_ly()
+    );
+}
@@ -0,0 +1,113 @@
+mod helpers;
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::mpsc;
+use std::thread;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::setup_default_logger;
+
+fn loop_rom() -> Vec<u8> {
+    let instructions = [
+        0x3C, // INC A
+        0xEA, 0x00, 0xC0, // LD (0xC000), A
+        0x18, 0xFA, // JR -6
+    ];
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+
+    rom_buffer
+}
+
+// Minimal client side of the remote serial protocol
+struct Client {
+    stream: TcpStream,
+}
+
+impl Client {
+    fn read_byte(&mut self) -> u8 {
+        let mut byte = [0];
+        self.stream.read_exact(&mut byte).unwrap();
+        byte[0]
+    }
+
+    fn request(&mut self, packet: &str) -> String {
+        let checksum = packet.bytes().fold(0u8, |sum, byte| sum.wrapping_add(byte));
+        write!(self.stream, "${}#{:02x}", packet, checksum).unwrap();
+        assert_eq!(self.read_byte(), b'+', "{} was not acknowledged", packet);
+
+        self.reply()
+    }
+
+    fn reply(&mut self) -> String {
+        assert_eq!(self.read_byte(), b'$');
+        let mut reply = vec![];
+        loop {
+            match self.read_byte() {
+                b'#' => break,
+                byte => reply.push(byte),
+            }
+        }
+        self.read_byte();
+        self.read_byte();
+        self.stream.write_all(b"+").unwrap();
+
+        String::from_utf8(reply).unwrap()
+    }
+}
+
+#[test]
+fn test_gdb_session() {
+    let _guard = setup_default_logger();
+
+    let (sender, receiver) = mpsc::channel();
+    let server = thread::spawn(move || {
+        let mut emu = Emulator::new_from_buffer(loop_rom(), true, None, None).unwrap();
+        let server = GdbServer::bind("127.0.0.1:0").unwrap();
+        sender.send(server.local_port().unwrap()).unwrap();
+
+        server.serve(&mut emu).unwrap();
+
+        (
+            emu.debugger.breakpoints().count(),
+            emu.debugger.watchpoints().count(),
+        )
+    });
+
+    let port = receiver.recv().unwrap();
+    let mut client = Client {
+        stream: TcpStream::connect(("127.0.0.1", port)).unwrap(),
+    };
+
+    assert_eq!(client.request("qSupported:swbreak+"), "PacketSize=1000");
+    assert_eq!(client.request("?"), "S05");
+    // PC is the last register
+    assert!(client.request("g").ends_with("0001"));
+    assert_eq!(client.request("m100,4"), "3cea00c0");
+
+    assert_eq!(client.request("Z1,104,1"), "OK");
+    assert_eq!(client.request("c"), "S05");
+    assert_eq!(client.request("p5"), "0401");
+    assert_eq!(client.request("z1,104,1"), "OK");
+
+    assert_eq!(client.request("Z2,c000,1"), "OK");
+    assert_eq!(client.request("c"), "T05watch:c000;");
+    // A starts at 0x01 and was incremented twice
+    assert_eq!(client.request("mc000,1"), "03");
+
+    assert_eq!(client.request("s"), "S05");
+    assert_eq!(client.request("p5"), "0001");
+
+    // break into a running program without breakpoints
+    assert_eq!(client.request("z2,c000,1"), "OK");
+    client.stream.write_all(b"$c#63").unwrap();
+    assert_eq!(client.read_byte(), b'+');
+    client.stream.write_all(&[0x03]).unwrap();
+    assert_eq!(client.reply(), "S02");
+
+    assert_eq!(client.request("D"), "OK");
+
+    // the session cleans up after itself
+    assert_eq!(server.join().unwrap(), (0, 0));
+}
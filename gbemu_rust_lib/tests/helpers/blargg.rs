@@ -0,0 +1,100 @@
+use tracing::info;
+
+use gbemu_rust_lib::prelude::*;
+
+// enough for the slowest complete suites
+pub const BLARGG_TIMEOUT_CYCLES: usize = 7200 * FRAME_CYCLES;
+
+// Result protocol of the ROMs that don't print over serial: the status byte at 0xA000 is 0x80
+// while the test is running and the result code afterwards, followed by a signature and the
+// zero terminated text output.
+const STATUS_ADDR: u16 = 0xA000;
+const SIGNATURE_ADDR: u16 = 0xA001;
+const TEXT_ADDR: u16 = 0xA004;
+const SIGNATURE: [u8; 3] = [0xDE, 0xB0, 0x61];
+const STATUS_RUNNING: u8 = 0x80;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BlarggResult {
+    Passed,
+    Failed { code: u8, output: String },
+    Timeout { output: String },
+}
+
+fn memory_text(emu: &Emulator) -> String {
+    (TEXT_ADDR..0xC000)
+        .map(|address| emu.system.read_byte(address))
+        .take_while(|byte| *byte != 0)
+        .map(char::from)
+        .collect()
+}
+
+fn memory_result(emu: &Emulator) -> Option<BlarggResult> {
+    let signature = [0, 1, 2].map(|offset| emu.system.read_byte(SIGNATURE_ADDR + offset));
+    if signature != SIGNATURE {
+        return None;
+    }
+
+    match emu.system.read_byte(STATUS_ADDR) {
+        STATUS_RUNNING => None,
+        0 => Some(BlarggResult::Passed),
+        code => Some(BlarggResult::Failed {
+            code,
+            output: memory_text(emu),
+        }),
+    }
+}
+
+fn serial_result(capture: &SerialCapture) -> Option<BlarggResult> {
+    if capture.contains("Passed") {
+        Some(BlarggResult::Passed)
+    } else if capture.contains("Failed") {
+        Some(BlarggResult::Failed {
+            code: 1,
+            output: capture.transcript(),
+        })
+    } else {
+        None
+    }
+}
+
+// Runs until the ROM reports a result over serial or through the memory signature
+pub fn run_blargg(rom: Vec<u8>, max_cycles: usize) -> BlarggResult {
+    let serial = CaptureSerial::new();
+    let capture = serial.capture();
+    let mut emu = Emulator::new_from_buffer(rom, true, None, Some(Box::new(serial))).unwrap();
+
+    for cycle in 0..max_cycles {
+        if let Err(err) = emu.step() {
+            panic!("Encountered error on cycle {}: {:02X?}", cycle, err);
+        }
+
+        // checking once per frame is plenty
+        if cycle % FRAME_CYCLES == 0
+            && let Some(result) = serial_result(&capture).or_else(|| memory_result(&emu))
+        {
+            info!("Finished after {} cycles: {:?}", cycle, result);
+            return result;
+        }
+    }
+
+    let output = if capture.is_empty() {
+        memory_text(&emu)
+    } else {
+        capture.transcript()
+    };
+
+    BlarggResult::Timeout { output }
+}
+
+pub fn test_blargg(rom_path: &str) {
+    let rom = std::fs::read(rom_path)
+        .unwrap_or_else(|err| panic!("Could not read ROM {}: {}", rom_path, err));
+
+    assert_eq!(
+        run_blargg(rom, BLARGG_TIMEOUT_CYCLES),
+        BlarggResult::Passed,
+        "{}",
+        rom_path
+    );
+}
@@ -1,5 +1,10 @@
 #![allow(dead_code)]
 
+pub mod blargg;
+pub mod mooneye;
+pub mod screenshot;
+pub mod single_step;
+
 use regex::Regex;
 use std::fmt;
 use std::fs::File;
@@ -68,10 +73,10 @@ where
     S: tracing_core::Subscriber,
     for<'a> S: LookupSpan<'a>,
 {
-    if let Some(parent_dir) = path.parent() {
-        if !parent_dir.try_exists().unwrap() {
-            std::fs::create_dir_all(parent_dir).unwrap();
-        }
+    if let Some(parent_dir) = path.parent()
+        && !parent_dir.try_exists().unwrap()
+    {
+        std::fs::create_dir_all(parent_dir).unwrap();
     }
 
     let trace_log_file = std::fs::OpenOptions::new()
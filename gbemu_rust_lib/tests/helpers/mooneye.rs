@@ -0,0 +1,63 @@
+use tracing::info;
+
+use gbemu_rust_lib::prelude::*;
+
+// mooneye tests finish within a few seconds, everything beyond that is a hang
+pub const MOONEYE_TIMEOUT_CYCLES: usize = 1200 * FRAME_CYCLES;
+
+const PASS_SIGNATURE: [u8; 6] = [3, 5, 8, 13, 21, 34];
+const FAIL_SIGNATURE: [u8; 6] = [0x42; 6];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MooneyeResult {
+    Passed,
+    Failed,
+    UnknownSignature([u8; 6]),
+    Timeout,
+}
+
+fn signature(registers: &Registers) -> [u8; 6] {
+    [
+        registers.b,
+        registers.c,
+        registers.d,
+        registers.e,
+        registers.h,
+        registers.l,
+    ]
+}
+
+// Runs until the test ROM executes the LD B,B breakpoint and decodes the register signature
+pub fn run_mooneye(rom: Vec<u8>, model: Model, max_cycles: usize) -> MooneyeResult {
+    let mut emu = Emulator::new_from_buffer_with_model(rom, model, None, true, None, None).unwrap();
+
+    for cycle in 0..max_cycles {
+        if let Err(err) = emu.step() {
+            panic!("Encountered error on cycle {}: {:02X?}", cycle, err);
+        }
+
+        if emu.cpu.at_software_breakpoint() {
+            info!("Reached breakpoint after {} cycles: {:?}", cycle, emu.cpu);
+
+            return match signature(&emu.cpu.registers) {
+                PASS_SIGNATURE => MooneyeResult::Passed,
+                FAIL_SIGNATURE => MooneyeResult::Failed,
+                signature => MooneyeResult::UnknownSignature(signature),
+            };
+        }
+    }
+
+    MooneyeResult::Timeout
+}
+
+pub fn test_mooneye(rom_path: &str) {
+    let rom = std::fs::read(rom_path)
+        .unwrap_or_else(|err| panic!("Could not read ROM {}: {}", rom_path, err));
+
+    assert_eq!(
+        run_mooneye(rom, Model::Dmg, MOONEYE_TIMEOUT_CYCLES),
+        MooneyeResult::Passed,
+        "{}",
+        rom_path
+    );
+}
@@ -0,0 +1,193 @@
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use tracing::info;
+
+use gbemu_rust_lib::prelude::*;
+
+pub const SCREENSHOTS_DIR: &str = "screenshots";
+
+// set this environment variable to overwrite the reference images with the current output
+pub const BLESS_ENV: &str = "GBEMU_BLESS";
+
+type Rgb = [u8; 3];
+
+pub struct RgbImage {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Rgb>,
+}
+
+impl RgbImage {
+    pub fn from_screenshot(screenshot: &Screenshot, palette: &Palette) -> Self {
+        Self {
+            width: screenshot.width,
+            height: screenshot.height,
+            pixels: screenshot
+                .pixels
+                .iter()
+                .map(|pixel| palette[<u8>::from(*pixel) as usize])
+                .collect(),
+        }
+    }
+
+    pub fn read_png(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|err| err.to_string())?;
+        let mut decoder = png::Decoder::new(std::io::BufReader::new(file));
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+
+        let mut reader = decoder.read_info().map_err(|err| err.to_string())?;
+        let mut buffer = vec![0; reader.output_buffer_size().unwrap_or_default()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|err| err.to_string())?;
+        let data = &buffer[..info.buffer_size()];
+
+        let pixels = match info.color_type {
+            png::ColorType::Grayscale => data.iter().map(|v| [*v, *v, *v]).collect(),
+            png::ColorType::GrayscaleAlpha => data.chunks(2).map(|c| [c[0], c[0], c[0]]).collect(),
+            png::ColorType::Rgb => data.chunks(3).map(|c| [c[0], c[1], c[2]]).collect(),
+            png::ColorType::Rgba => data.chunks(4).map(|c| [c[0], c[1], c[2]]).collect(),
+            png::ColorType::Indexed => {
+                return Err("Indexed images should have been expanded".to_owned());
+            },
+        };
+
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            pixels,
+        })
+    }
+
+    pub fn write_png(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent_dir) = path.parent() {
+            std::fs::create_dir_all(parent_dir).map_err(|err| err.to_string())?;
+        }
+
+        let file = File::create(path).map_err(|err| err.to_string())?;
+        let mut encoder =
+            png::Encoder::new(BufWriter::new(file), self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = self.pixels.iter().flatten().copied().collect();
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&data))
+            .map_err(|err| err.to_string())
+    }
+
+    // mismatching pixels are red, matching pixels are faded out
+    pub fn diff(&self, reference: &RgbImage) -> (usize, RgbImage) {
+        let mut mismatches = 0;
+        let pixels = self
+            .pixels
+            .iter()
+            .zip(&reference.pixels)
+            .map(|(actual, expected)| {
+                if actual == expected {
+                    actual.map(|v| 0xC0 + v / 4)
+                } else {
+                    mismatches += 1;
+                    [0xFF, 0x00, 0x00]
+                }
+            })
+            .collect();
+
+        (
+            mismatches,
+            RgbImage {
+                width: self.width,
+                height: self.height,
+                pixels,
+            },
+        )
+    }
+}
+
+pub fn run_frames(rom: Vec<u8>, model: Model, frames: usize) -> Emulator {
+    let mut emu = Emulator::new_from_buffer_with_model(rom, model, None, true, None, None).unwrap();
+
+    for cycle in 0..frames * FRAME_CYCLES {
+        if let Err(err) = emu.step() {
+            panic!("Encountered error on cycle {}: {:02X?}", cycle, err);
+        }
+    }
+
+    emu
+}
+
+// Runs the ROM for a fixed number of frames and compares the framebuffer to the reference image
+pub fn test_screenshot(name: &str, rom_path: &str, reference_path: &str, frames: usize) {
+    let rom = std::fs::read(rom_path)
+        .unwrap_or_else(|err| panic!("Could not read ROM {}: {}", rom_path, err));
+
+    let emu = run_frames(rom, Model::Dmg, frames);
+    assert_screenshot(name, &emu, reference_path);
+}
+
+pub fn assert_screenshot(name: &str, emu: &Emulator, reference_path: &str) {
+    let reference_path = Path::new(reference_path);
+    let actual = RgbImage::from_screenshot(&emu.screenshot(), &GRAYSCALE_PALETTE);
+    let actual_path = actual_path(name);
+
+    if std::env::var_os(BLESS_ENV).is_some() {
+        info!("Writing reference image {}", reference_path.display());
+        actual.write_png(reference_path).unwrap();
+        return;
+    }
+
+    let reference = match RgbImage::read_png(reference_path) {
+        Ok(reference) => reference,
+        Err(err) => {
+            actual.write_png(&actual_path).unwrap();
+            panic!(
+                "Could not read reference image {}: {}. The current output was written to {}, \
+                 set {} to use it as reference",
+                reference_path.display(),
+                err,
+                actual_path.display(),
+                BLESS_ENV
+            );
+        },
+    };
+
+    assert_image(
+        name,
+        &actual,
+        &reference,
+        &reference_path.display().to_string(),
+    );
+}
+
+fn actual_path(name: &str) -> PathBuf {
+    PathBuf::from(SCREENSHOTS_DIR).join(format!("{}.png", name))
+}
+
+// Compares the output to a reference which doesn't come from a file, e.g. one built by the test
+pub fn assert_image(name: &str, actual: &RgbImage, reference: &RgbImage, reference_name: &str) {
+    if (actual.width, actual.height) != (reference.width, reference.height) {
+        panic!(
+            "Reference image {} has size {}x{}, expected {}x{}",
+            reference_name, reference.width, reference.height, actual.width, actual.height
+        );
+    }
+
+    let (mismatches, diff) = actual.diff(reference);
+    if mismatches > 0 {
+        let actual_path = actual_path(name);
+        let diff_path = PathBuf::from(SCREENSHOTS_DIR).join(format!("{}.diff.png", name));
+        actual.write_png(&actual_path).unwrap();
+        diff.write_png(&diff_path).unwrap();
+
+        panic!(
+            "{} pixels differ from {}. See {} and {}",
+            mismatches,
+            reference_name,
+            actual_path.display(),
+            diff_path.display()
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use std::path::Path;
+
+use serde_json::Value;
+use tracing::{info, warn};
+
+use gbemu_rust_lib::prelude::*;
+
+const IE_ADDR: u16 = 0xFFFF;
+const IF_ADDR: u16 = 0xFF0F;
+
+// no instruction takes longer than 6 M-cycles, the rest is a safety margin for HALT and friends
+const MAX_CYCLES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Access {
+    Read,
+    Write,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BusActivity {
+    pub address: u16,
+    pub value: u8,
+    pub access: Access,
+}
+
+// 64 KiB of RAM without any memory mapped hardware, records every access
+pub struct FlatBus {
+    pub memory: Vec<u8>,
+    pub activity: Vec<BusActivity>,
+}
+
+impl FlatBus {
+    pub fn new() -> Self {
+        Self {
+            memory: vec![0; 0x10000],
+            activity: vec![],
+        }
+    }
+}
+
+impl Bus for FlatBus {
+    fn read_byte(&mut self, address: u16) -> u8 {
+        let value = self.memory[address as usize];
+        self.activity.push(BusActivity {
+            address,
+            value,
+            access: Access::Read,
+        });
+
+        value
+    }
+
+    fn write_byte(&mut self, address: u16, value: u8) {
+        self.memory[address as usize] = value;
+        self.activity.push(BusActivity {
+            address,
+            value,
+            access: Access::Write,
+        });
+    }
+
+    fn peek_byte(&self, address: u16) -> u8 {
+        self.memory[address as usize]
+    }
+
+    fn interrupt_enable(&self) -> u8 {
+        self.memory[IE_ADDR as usize]
+    }
+
+    fn interrupt_flags(&self) -> u8 {
+        self.memory[IF_ADDR as usize]
+    }
+
+    fn set_interrupt_flags(&mut self, value: u8) {
+        self.memory[IF_ADDR as usize] = value;
+    }
+}
+
+fn field(state: &Value, name: &str) -> Result<u16, String> {
+    state[name]
+        .as_u64()
+        .map(|value| value as u16)
+        .ok_or_else(|| format!("Missing field '{}'", name))
+}
+
+fn ram(state: &Value) -> Result<Vec<(u16, u8)>, String> {
+    state["ram"]
+        .as_array()
+        .ok_or("Missing field 'ram'")?
+        .iter()
+        .map(|entry| match (entry[0].as_u64(), entry[1].as_u64()) {
+            (Some(address), Some(value)) => Ok((address as u16, value as u8)),
+            _ => Err(format!("Invalid RAM entry {}", entry)),
+        })
+        .collect()
+}
+
+// cycles look like [address, value, "r-m"], idle cycles have "---" or are null
+fn cycles(test: &Value) -> Result<Vec<Option<BusActivity>>, String> {
+    test["cycles"]
+        .as_array()
+        .ok_or("Missing field 'cycles'")?
+        .iter()
+        .map(|cycle| {
+            let access = match cycle[2].as_str() {
+                Some(pins) if pins.contains('r') => Access::Read,
+                Some(pins) if pins.contains('w') => Access::Write,
+                _ => return Ok(None),
+            };
+
+            match (cycle[0].as_u64(), cycle[1].as_u64()) {
+                (Some(address), Some(value)) => Ok(Some(BusActivity {
+                    address: address as u16,
+                    value: value as u8,
+                    access,
+                })),
+                _ => Err(format!("Invalid cycle {}", cycle)),
+            }
+        })
+        .collect()
+}
+
+fn setup(initial: &Value) -> Result<(Cpu, FlatBus), String> {
+    let registers = Registers {
+        a: field(initial, "a")? as u8,
+        f: field(initial, "f")? as u8,
+        b: field(initial, "b")? as u8,
+        c: field(initial, "c")? as u8,
+        d: field(initial, "d")? as u8,
+        e: field(initial, "e")? as u8,
+        h: field(initial, "h")? as u8,
+        l: field(initial, "l")? as u8,
+        sp: field(initial, "sp")?,
+        // the opcode at PC - 1 has already been fetched
+        pc: field(initial, "pc")?.wrapping_sub(1),
+        ..Default::default()
+    };
+
+    let mut bus = FlatBus::new();
+    for (address, value) in ram(initial)? {
+        bus.memory[address as usize] = value;
+    }
+    if let Ok(ie) = field(initial, "ie") {
+        bus.memory[IE_ADDR as usize] = ie as u8;
+    }
+
+    let mut cpu = Cpu::new_from_registers(registers);
+    cpu.interrupt_enabled = field(initial, "ime")? != 0;
+    cpu.prefetch(&mut bus);
+    bus.activity.clear();
+
+    Ok((cpu, bus))
+}
+
+// Executes exactly one instruction, including the fetch of the next opcode
+fn execute(cpu: &mut Cpu, bus: &mut FlatBus) -> Result<Vec<Vec<BusActivity>>, String> {
+    let mut cycles = vec![];
+
+    for _ in 0..MAX_CYCLES {
+        let completed = cpu
+            .cycle(bus)
+            .map_err(|err| format!("Execution error: {:?}", err))?;
+        cycles.push(std::mem::take(&mut bus.activity));
+
+        if completed {
+            return Ok(cycles);
+        }
+    }
+
+    Err(format!(
+        "Instruction did not complete within {} M-cycles",
+        MAX_CYCLES
+    ))
+}
+
+fn compare(cpu: &Cpu, bus: &FlatBus, expected: &Value) -> Result<(), String> {
+    let registers = [
+        ("a", cpu.registers.a as u16),
+        ("f", cpu.registers.f as u16),
+        ("b", cpu.registers.b as u16),
+        ("c", cpu.registers.c as u16),
+        ("d", cpu.registers.d as u16),
+        ("e", cpu.registers.e as u16),
+        ("h", cpu.registers.h as u16),
+        ("l", cpu.registers.l as u16),
+        ("sp", cpu.registers.sp),
+        ("pc", cpu.registers.pc),
+        (
+            "ime",
+            (cpu.interrupt_enabled || cpu.interrupt_enable_pending) as u16,
+        ),
+    ];
+
+    for (name, actual) in registers {
+        let expected = field(expected, name)?;
+        if actual != expected {
+            return Err(format!(
+                "Register {}: expected {:04X}, got {:04X}",
+                name, expected, actual
+            ));
+        }
+    }
+
+    for (address, expected) in ram(expected)? {
+        let actual = bus.memory[address as usize];
+        if actual != expected {
+            return Err(format!(
+                "Memory {:04X}: expected {:02X}, got {:02X}",
+                address, expected, actual
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn run_test_vector(test: &Value) -> Result<(), String> {
+    let (mut cpu, mut bus) = setup(&test["initial"])?;
+    let actual_cycles = execute(&mut cpu, &mut bus)?;
+    let expected_cycles = cycles(test)?;
+
+    compare(&cpu, &bus, &test["final"])?;
+
+    if actual_cycles.len() != expected_cycles.len() {
+        return Err(format!(
+            "Expected {} M-cycles, took {}",
+            expected_cycles.len(),
+            actual_cycles.len()
+        ));
+    }
+
+    for (cycle, (actual, expected)) in actual_cycles.iter().zip(&expected_cycles).enumerate() {
+        if actual.as_slice() != expected.as_slice() {
+            return Err(format!(
+                "M-cycle {}: expected bus activity {:02X?}, got {:02X?}",
+                cycle, expected, actual
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+// Runs all test vectors of one JSON file and returns the failures
+pub fn run_test_file(path: &Path) -> Vec<String> {
+    let tests: Value = serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+    tests
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|test| {
+            run_test_vector(test)
+                .err()
+                .map(|err| format!("{}: {}", test["name"], err))
+        })
+        .collect()
+}
+
+pub fn test_single_step_dir(dir: &str) {
+    let entries = std::fs::read_dir(dir)
+        .unwrap_or_else(|err| panic!("Could not read test vectors {}: {}", dir, err));
+
+    let mut paths: Vec<_> = entries
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    paths.sort();
+    assert!(!paths.is_empty(), "No test vectors found in {}", dir);
+
+    let mut failed_files = vec![];
+    for path in &paths {
+        let failures = run_test_file(path);
+        if let Some(first) = failures.first() {
+            warn!(
+                "{}: {} failures, first: {}",
+                path.display(),
+                failures.len(),
+                first
+            );
+            failed_files.push(path.file_name().unwrap().to_string_lossy().into_owned());
+        }
+    }
+
+    info!(
+        "{} of {} files passed",
+        paths.len() - failed_files.len(),
+        paths.len()
+    );
+    assert!(
+        failed_files.is_empty(),
+        "Failed: {}",
+        failed_files.join(", ")
+    );
+}
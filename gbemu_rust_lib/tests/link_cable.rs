@@ -0,0 +1,65 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::setup_default_logger;
+
+fn transfer_rom(data: u8, control: u8) -> Vec<u8> {
+    let instructions = [
+        0x3E, data, // LD A, data
+        0xE0, 0x01, // LDH (SB), A
+        0x3E, control, // LD A, control
+        0xE0, 0x02, // LDH (SC), A
+        0xF0, 0x02, // LDH A, (SC)
+        0xCB, 0x7F, // BIT 7, A
+        0x20, 0xFA, // JR NZ, -6
+        0xF0, 0x01, // LDH A, (SB)
+        0x47, // LD B, A
+        0x18, 0xFE, // JR -2
+    ];
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+
+    rom_buffer
+}
+
+#[test]
+fn test_link_cable_exchange() {
+    let _guard = setup_default_logger();
+
+    let mut link =
+        LinkCable::new_from_buffers([transfer_rom(0x42, 0x81), transfer_rom(0x24, 0x80)], false)
+            .unwrap();
+
+    for _ in 0..4096 {
+        link.step().unwrap();
+    }
+
+    let [master, slave] = &link.emulators;
+
+    assert_eq!(master.cpu.registers.b, 0x24);
+    assert_eq!(slave.cpu.registers.b, 0x42);
+
+    // serial interrupt requested on both sides
+    assert_eq!(master.system.read_byte(0xFF0F) & 0x08, 0x08);
+    assert_eq!(slave.system.read_byte(0xFF0F) & 0x08, 0x08);
+}
+
+#[test]
+fn test_link_cable_slave_waits_for_clock() {
+    let _guard = setup_default_logger();
+
+    let mut link =
+        LinkCable::new_from_buffers([transfer_rom(0x42, 0x80), transfer_rom(0x24, 0x80)], false)
+            .unwrap();
+
+    for _ in 0..4096 {
+        link.step().unwrap();
+    }
+
+    let [first, second] = &link.emulators;
+
+    assert_eq!(first.system.read_byte(0xFF01), 0x42);
+    assert_eq!(second.system.read_byte(0xFF01), 0x24);
+    assert_eq!(first.system.read_byte(0xFF02) & 0x80, 0x80);
+    assert_eq!(second.system.read_byte(0xFF02) & 0x80, 0x80);
+}
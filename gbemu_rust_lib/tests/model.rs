@@ -0,0 +1,130 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::setup_default_logger;
+
+fn empty_rom(cgb_flag: u8) -> Vec<u8> {
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100] = 0x76; // HALT
+    rom_buffer[0x0143] = cgb_flag;
+
+    rom_buffer
+}
+
+fn new_emulator(model: Model, cgb_flag: u8) -> Emulator {
+    Emulator::new_from_buffer_with_model(empty_rom(cgb_flag), model, None, false, None, None)
+        .unwrap()
+}
+
+#[test]
+fn test_initial_w_ram() {
+    let _guard = setup_default_logger();
+
+    let dmg = new_emulator(Model::Dmg, 0x00);
+    let w_ram: Vec<u8> = (0xC000..0xE000).map(|a| dmg.system.read_byte(a)).collect();
+    assert!(w_ram.iter().any(|b| *b != 0x00));
+    assert_eq!(dmg.model(), Model::Dmg);
+
+    let cgb = new_emulator(Model::Cgb, 0x80);
+    assert!((0xC000..0xE000).all(|a| cgb.system.read_byte(a) == 0x00));
+
+    // high ram is always cleared
+    assert!((0xFF80..0xFFFF).all(|a| dmg.system.read_byte(a) == 0x00));
+}
+
+#[test]
+fn test_cgb_w_ram_banking() {
+    let _guard = setup_default_logger();
+
+    let mut emu = new_emulator(Model::Cgb, 0x80);
+    assert!(emu.system.cgb_mode());
+    assert_eq!(emu.system.read_byte(0xFF70), 0xF9);
+
+    emu.system.write_byte(0xD000, 0x11);
+    emu.system.write_byte(0xFF70, 0x02);
+    assert_eq!(emu.system.read_byte(0xFF70), 0xFA);
+    assert_eq!(emu.system.read_byte(0xD000), 0x00);
+    emu.system.write_byte(0xD000, 0x22);
+
+    // bank 0 selects bank 1
+    emu.system.write_byte(0xFF70, 0x00);
+    assert_eq!(emu.system.read_byte(0xFF70), 0xF9);
+    assert_eq!(emu.system.read_byte(0xD000), 0x11);
+    assert_eq!(emu.system.read_byte(0xF000), 0x11);
+
+    emu.system.write_byte(0xFF70, 0x02);
+    assert_eq!(emu.system.read_byte(0xD000), 0x22);
+
+    // bank 0 is fixed
+    emu.system.write_byte(0xC000, 0x33);
+    emu.system.write_byte(0xFF70, 0x07);
+    assert_eq!(emu.system.read_byte(0xC000), 0x33);
+}
+
+#[test]
+fn test_cgb_v_ram_banking() {
+    let _guard = setup_default_logger();
+
+    let mut emu = new_emulator(Model::Cgb, 0x80);
+    assert_eq!(emu.system.read_byte(0xFF4F), 0xFE);
+
+    emu.system.write_byte(0x8010, 0x11);
+    emu.system.write_byte(0x9800, 0x01);
+    emu.system.write_byte(0xFF4F, 0xFF);
+    assert_eq!(emu.system.read_byte(0xFF4F), 0xFF);
+    assert_eq!(emu.system.v_ram_bank(), 1);
+    assert_eq!(emu.system.read_byte(0x8010), 0x00);
+    assert_eq!(emu.system.read_byte(0x9800), 0x00);
+
+    // bank 1 holds the second tile set and the tile attributes
+    emu.system.write_byte(0x8010, 0x22);
+    emu.system.write_byte(0x9C00, 0x08);
+    let graphics = &emu.system.graphics;
+    assert_eq!(graphics.tile_data_bank_1.get_byte(0x0010), 0x22);
+    assert_eq!(graphics.attribute_maps[1].tiles[0][0], 0x08);
+    assert_eq!(graphics.tile_maps[1].tiles[0][0], 0x00);
+
+    emu.system.write_byte(0xFF4F, 0x00);
+    assert_eq!(emu.system.read_byte(0x8010), 0x11);
+    assert_eq!(emu.system.read_byte(0x9800), 0x01);
+}
+
+#[test]
+fn test_cgb_features_unavailable() {
+    let _guard = setup_default_logger();
+
+    for mut emu in [
+        new_emulator(Model::Dmg, 0x80),
+        new_emulator(Model::Cgb, 0x00),
+    ] {
+        assert!(!emu.system.cgb_mode());
+        assert_eq!(emu.system.read_byte(0xFF70), 0xFF);
+        assert_eq!(emu.system.read_byte(0xFF4F), 0xFF);
+
+        emu.system.write_byte(0xD000, 0x11);
+        emu.system.write_byte(0xFF70, 0x02);
+        assert_eq!(emu.system.read_byte(0xD000), 0x11);
+
+        emu.system.write_byte(0x8000, 0x11);
+        emu.system.write_byte(0xFF4F, 0x01);
+        assert_eq!(emu.system.read_byte(0x8000), 0x11);
+    }
+}
+
+#[test]
+fn test_unusable_memory_quirk() {
+    let _guard = setup_default_logger();
+
+    assert_eq!(
+        new_emulator(Model::Dmg, 0x00).system.read_byte(0xFEB2),
+        0x00
+    );
+    assert_eq!(
+        new_emulator(Model::Cgb, 0x80).system.read_byte(0xFEB2),
+        0x00
+    );
+    assert_eq!(
+        new_emulator(Model::Agb, 0x80).system.read_byte(0xFEB2),
+        0xBB
+    );
+}
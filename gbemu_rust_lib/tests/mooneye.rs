@@ -0,0 +1,127 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::mooneye::{MOONEYE_TIMEOUT_CYCLES, MooneyeResult, run_mooneye, test_mooneye};
+use helpers::setup_default_logger;
+
+// The ROMs have to be built with make first, run them with --ignored
+macro_rules! mooneye_tests {
+    ($reason:literal; $($name:ident: $path:literal,)*) => {
+        $(
+            #[test]
+            #[ignore = $reason]
+            fn $name() {
+                let _guard = setup_default_logger();
+
+                test_mooneye(concat!("../external/test_roms/mooneye/build/", $path));
+            }
+        )*
+    };
+}
+
+mooneye_tests! {
+    "needs the mooneye ROMs built with make";
+    test_timer_div_write: "acceptance/timer/div_write.gb",
+    test_timer_rapid_toggle: "acceptance/timer/rapid_toggle.gb",
+    test_timer_tim00: "acceptance/timer/tim00.gb",
+    test_timer_tim00_div_trigger: "acceptance/timer/tim00_div_trigger.gb",
+    test_timer_tim01: "acceptance/timer/tim01.gb",
+    test_timer_tim01_div_trigger: "acceptance/timer/tim01_div_trigger.gb",
+    test_timer_tim10: "acceptance/timer/tim10.gb",
+    test_timer_tim10_div_trigger: "acceptance/timer/tim10_div_trigger.gb",
+    test_timer_tim11: "acceptance/timer/tim11.gb",
+    test_timer_tim11_div_trigger: "acceptance/timer/tim11_div_trigger.gb",
+    test_timer_tima_reload: "acceptance/timer/tima_reload.gb",
+    test_timer_tima_write_reloading: "acceptance/timer/tima_write_reloading.gb",
+    test_timer_tma_write_reloading: "acceptance/timer/tma_write_reloading.gb",
+    test_interrupts_ie_push: "acceptance/interrupts/ie_push.gb",
+    test_oam_dma_basic: "acceptance/oam_dma/basic.gb",
+    test_oam_dma_reg_read: "acceptance/oam_dma/reg_read.gb",
+    test_oam_dma_sources_gs: "acceptance/oam_dma/sources-GS.gb",
+    test_oam_dma_restart: "acceptance/oam_dma_restart.gb",
+    test_oam_dma_start: "acceptance/oam_dma_start.gb",
+    test_oam_dma_timing: "acceptance/oam_dma_timing.gb",
+    test_ei_sequence: "acceptance/ei_sequence.gb",
+    test_ei_timing: "acceptance/ei_timing.gb",
+    test_di_timing_gs: "acceptance/di_timing-GS.gb",
+    test_halt_ime0_ei: "acceptance/halt_ime0_ei.gb",
+    test_halt_ime0_nointr_timing: "acceptance/halt_ime0_nointr_timing.gb",
+    test_halt_ime1_timing: "acceptance/halt_ime1_timing.gb",
+    test_if_ie_registers: "acceptance/if_ie_registers.gb",
+    test_intr_timing: "acceptance/intr_timing.gb",
+    test_rapid_di_ei: "acceptance/rapid_di_ei.gb",
+    test_reti_intr_timing: "acceptance/reti_intr_timing.gb",
+    test_reti_timing: "acceptance/reti_timing.gb",
+    test_call_timing: "acceptance/call_timing.gb",
+    test_ret_timing: "acceptance/ret_timing.gb",
+    test_div_timing: "acceptance/div_timing.gb",
+    test_push_timing: "acceptance/push_timing.gb",
+    test_pop_timing: "acceptance/pop_timing.gb",
+    test_jp_timing: "acceptance/jp_timing.gb",
+    test_ld_hl_sp_e_timing: "acceptance/ld_hl_sp_e_timing.gb",
+    test_add_sp_e_timing: "acceptance/add_sp_e_timing.gb",
+    test_ppu_hblank_ly_scx_timing_gs: "acceptance/ppu/hblank_ly_scx_timing-GS.gb",
+    test_ppu_intr_1_2_timing_gs: "acceptance/ppu/intr_1_2_timing-GS.gb",
+    test_ppu_intr_2_0_timing: "acceptance/ppu/intr_2_0_timing.gb",
+    test_ppu_intr_2_mode0_timing: "acceptance/ppu/intr_2_mode0_timing.gb",
+    test_ppu_intr_2_mode3_timing: "acceptance/ppu/intr_2_mode3_timing.gb",
+    test_ppu_intr_2_oam_ok_timing: "acceptance/ppu/intr_2_oam_ok_timing.gb",
+    test_ppu_intr_2_mode0_timing_sprites: "acceptance/ppu/intr_2_mode0_timing_sprites.gb",
+    test_ppu_lcdon_timing_gs: "acceptance/ppu/lcdon_timing-GS.gb",
+    test_ppu_lcdon_write_timing_gs: "acceptance/ppu/lcdon_write_timing-GS.gb",
+    test_ppu_stat_irq_blocking: "acceptance/ppu/stat_irq_blocking.gb",
+    test_ppu_stat_lyc_onoff: "acceptance/ppu/stat_lyc_onoff.gb",
+    test_ppu_vblank_stat_intr_gs: "acceptance/ppu/vblank_stat_intr-GS.gb",
+    test_mbc1_bits_bank1: "emulator-only/mbc1/bits_bank1.gb",
+    test_mbc1_bits_bank2: "emulator-only/mbc1/bits_bank2.gb",
+    test_mbc1_bits_mode: "emulator-only/mbc1/bits_mode.gb",
+    test_mbc1_bits_ramg: "emulator-only/mbc1/bits_ramg.gb",
+    test_mbc1_ram_64kb: "emulator-only/mbc1/ram_64kb.gb",
+    test_mbc1_ram_256kb: "emulator-only/mbc1/ram_256kb.gb",
+    test_mbc1_rom_512kb: "emulator-only/mbc1/rom_512kb.gb",
+    test_mbc1_rom_1mb: "emulator-only/mbc1/rom_1Mb.gb",
+    test_mbc1_rom_2mb: "emulator-only/mbc1/rom_2Mb.gb",
+    test_mbc1_rom_4mb: "emulator-only/mbc1/rom_4Mb.gb",
+    test_mbc1_rom_8mb: "emulator-only/mbc1/rom_8Mb.gb",
+    test_mbc1_rom_16mb: "emulator-only/mbc1/rom_16Mb.gb",
+}
+
+mooneye_tests! {
+    "MBC1 multicarts are not emulated yet";
+    test_mbc1_multicart_rom_8mb: "emulator-only/mbc1/multicart_rom_8Mb.gb",
+}
+
+fn signature_rom(signature: [u8; 6]) -> Vec<u8> {
+    let [b, c, d, e, h, l] = signature;
+    let instructions = [
+        0x06, b, // LD B, b
+        0x0E, c, // LD C, c
+        0x16, d, // LD D, d
+        0x1E, e, // LD E, e
+        0x26, h, // LD H, h
+        0x2E, l,    // LD L, l
+        0x40, // LD B, B
+        0x18, 0xFE, // JR -2
+    ];
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+
+    rom_buffer
+}
+
+#[test]
+fn test_signature() {
+    let _guard = setup_default_logger();
+
+    let run = |signature| run_mooneye(signature_rom(signature), Model::Dmg, MOONEYE_TIMEOUT_CYCLES);
+    assert_eq!(run([3, 5, 8, 13, 21, 34]), MooneyeResult::Passed);
+    assert_eq!(run([0x42; 6]), MooneyeResult::Failed);
+    assert_eq!(
+        run([3, 5, 8, 13, 21, 0]),
+        MooneyeResult::UnknownSignature([3, 5, 8, 13, 21, 0])
+    );
+
+    let mut rom = signature_rom([0; 6]);
+    rom[0x010C] = 0x00;
+    assert_eq!(run_mooneye(rom, Model::Dmg, 1000), MooneyeResult::Timeout);
+}
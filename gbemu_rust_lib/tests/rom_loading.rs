@@ -0,0 +1,135 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::setup_default_logger;
+
+fn load(rom: Vec<u8>) -> Result<Emulator, LoadError> {
+    Emulator::new_from_buffer(rom, false, None, None)
+}
+
+fn header_rom(size: usize, address: usize, value: u8) -> Vec<u8> {
+    let mut rom_buffer = vec![0; size];
+    rom_buffer[address] = value;
+
+    rom_buffer
+}
+
+#[test]
+fn test_truncated_rom() {
+    let _guard = setup_default_logger();
+
+    assert_eq!(
+        load(vec![]).err(),
+        Some(LoadError::TruncatedRom {
+            expected: 0x0150,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        load(vec![0; 0x4000]).err(),
+        Some(LoadError::TruncatedRom {
+            expected: 0x8000,
+            actual: 0x4000
+        })
+    );
+}
+
+#[test]
+fn test_size_mismatch() {
+    let _guard = setup_default_logger();
+
+    assert_eq!(
+        load(vec![0; 0x8001]).err(),
+        Some(LoadError::SizeMismatch {
+            name: "ROM",
+            expected: 0x8000,
+            actual: 0x8001
+        })
+    );
+}
+
+#[test]
+fn test_invalid_header_field() {
+    let _guard = setup_default_logger();
+
+    assert_eq!(
+        load(header_rom(0x8000, 0x0148, 0x42)).err(),
+        Some(LoadError::InvalidHeaderField {
+            field: "ROM size",
+            value: 0x42
+        })
+    );
+}
+
+#[test]
+fn test_unsupported_mapper() {
+    let _guard = setup_default_logger();
+
+    let error = load(header_rom(0x8000, 0x0147, 0x05)).err().unwrap();
+    assert_eq!(
+        error,
+        LoadError::UnsupportedMapper {
+            cartridge_type: 0x05
+        }
+    );
+    assert_eq!(error.to_string(), "Unsupported cartridge type 0x05");
+}
+
+#[test]
+fn test_boot_rom_size_mismatch() {
+    let _guard = setup_default_logger();
+
+    let error = Emulator::new_from_buffer_with_model(
+        vec![0; 0x8000],
+        Model::Dmg,
+        Some(vec![0; 0x0900]),
+        false,
+        None,
+        None,
+    )
+    .err()
+    .unwrap();
+
+    assert_eq!(
+        error.to_string(),
+        "The boot ROM has to be 256 bytes big. Got: 2304"
+    );
+}
+
+#[test]
+fn test_invalid_ram_size() {
+    let _guard = setup_default_logger();
+
+    assert_eq!(
+        load(header_rom(0x8000, 0x0149, 0x06)).err(),
+        Some(LoadError::InvalidHeaderField {
+            field: "RAM size",
+            value: 0x06
+        })
+    );
+}
+
+#[test]
+fn test_cartridge_header() {
+    let _guard = setup_default_logger();
+
+    let mut rom_buffer = vec![0; 0x10000];
+    rom_buffer[0x0134..0x013A].copy_from_slice(b"GBEMU!");
+    rom_buffer[0x0147] = 0x01;
+    rom_buffer[0x0148] = 0x01;
+
+    let emu = load(rom_buffer).unwrap();
+    let header = emu.cartridge_header();
+
+    assert_eq!(header.title, "GBEMU!");
+    assert_eq!(
+        header.mbc_type(),
+        Ok(MbcType::Mbc1 {
+            ram: false,
+            battery: false
+        })
+    );
+    assert_eq!(header.rom_banks, 4);
+    assert_eq!(header.ram_banks, 0);
+    assert!(!header.header_checksum.is_valid());
+}
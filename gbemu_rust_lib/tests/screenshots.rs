@@ -0,0 +1,140 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::screenshot::{RgbImage, assert_image, run_frames, test_screenshot};
+use helpers::setup_default_logger;
+
+const DMG_ACID2_FRAMES: usize = 10;
+const MEALYBUG_FRAMES: usize = 20;
+const BLARGG_FRAMES: usize = 3600;
+
+fn stripes_rom() -> Vec<u8> {
+    let instructions = [
+        0x21, 0x00, 0x80, // LD HL, 0x8000
+        0x06, 0x10, // LD B, 16
+        0x3E, 0xF0, // LD A, 0xF0
+        0x22, // LD (HL+), A
+        0x05, // DEC B
+        0x20, 0xFA, // JR NZ, -6
+        0x18, 0xFE, // JR -2
+    ];
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+
+    rom_buffer
+}
+
+#[test]
+fn test_stripes() {
+    let _guard = setup_default_logger();
+
+    // every map entry is tile 0, which is color 3 on the left and color 0 on the right half of
+    // each row. BGP is 0xFC after boot, which keeps both colors.
+    let reference = RgbImage {
+        width: LCD_WIDTH,
+        height: LCD_HEIGHT,
+        pixels: (0..LCD_WIDTH * LCD_HEIGHT)
+            .map(|index| match index % 8 {
+                0..4 => GRAYSCALE_PALETTE[3],
+                _ => GRAYSCALE_PALETTE[0],
+            })
+            .collect(),
+    };
+
+    let emu = run_frames(stripes_rom(), Model::Dmg, 2);
+    let actual = RgbImage::from_screenshot(&emu.screenshot(), &GRAYSCALE_PALETTE);
+    assert_image("stripes", &actual, &reference, "4 pixel wide stripes");
+}
+
+#[test]
+fn test_diff() {
+    let _guard = setup_default_logger();
+
+    let mut screenshot = run_frames(stripes_rom(), Model::Dmg, 2).screenshot();
+    let reference = RgbImage::from_screenshot(&screenshot, &GRAYSCALE_PALETTE);
+
+    screenshot.pixels[0] = if screenshot.pixels[0] == Pixel::Color0 {
+        Pixel::Color3
+    } else {
+        Pixel::Color0
+    };
+    let actual = RgbImage::from_screenshot(&screenshot, &GRAYSCALE_PALETTE);
+
+    let (mismatches, diff) = actual.diff(&reference);
+    assert_eq!(mismatches, 1);
+    assert_eq!(diff.pixels[0], [0xFF, 0x00, 0x00]);
+    assert_ne!(diff.pixels[1], [0xFF, 0x00, 0x00]);
+}
+
+#[test]
+fn test_dmg_acid2() {
+    let _guard = setup_default_logger();
+
+    test_screenshot(
+        "dmg-acid2",
+        "../external/test_roms/dmg-acid2/dmg-acid2.gb",
+        "../external/test_roms/dmg-acid2/img/reference-dmg.png",
+        DMG_ACID2_FRAMES,
+    );
+}
+
+// The ROMs have to be built with make first, the expected images ship with the suite
+macro_rules! mealybug_tests {
+    ($($name:ident: $rom:literal,)*) => {
+        $(
+            #[test]
+            fn $name() {
+                let _guard = setup_default_logger();
+
+                test_screenshot(
+                    $rom,
+                    concat!("../external/test_roms/mealybug/build/ppu/", $rom, ".gb"),
+                    concat!("../external/test_roms/mealybug/expected/DMG-blob/", $rom, ".png"),
+                    MEALYBUG_FRAMES,
+                );
+            }
+        )*
+    };
+}
+
+mealybug_tests! {
+    test_mealybug_m2_win_en_toggle: "m2_win_en_toggle",
+    test_mealybug_m3_bgp_change: "m3_bgp_change",
+    test_mealybug_m3_bgp_change_sprites: "m3_bgp_change_sprites",
+    test_mealybug_m3_lcdc_bg_en_change: "m3_lcdc_bg_en_change",
+    test_mealybug_m3_lcdc_bg_map_change: "m3_lcdc_bg_map_change",
+    test_mealybug_m3_lcdc_obj_en_change: "m3_lcdc_obj_en_change",
+    test_mealybug_m3_lcdc_obj_size_change: "m3_lcdc_obj_size_change",
+    test_mealybug_m3_lcdc_tile_sel_change: "m3_lcdc_tile_sel_change",
+    test_mealybug_m3_lcdc_win_en_change_multiple: "m3_lcdc_win_en_change_multiple",
+    test_mealybug_m3_obp0_change: "m3_obp0_change",
+    test_mealybug_m3_scx_high_5_bits: "m3_scx_high_5_bits",
+    test_mealybug_m3_scx_low_3_bits: "m3_scx_low_3_bits",
+    test_mealybug_m3_scy_change: "m3_scy_change",
+    test_mealybug_m3_window_timing: "m3_window_timing",
+    test_mealybug_m3_wx_4_change: "m3_wx_4_change",
+}
+
+#[test]
+fn test_blargg_cpu_instrs_screen() {
+    let _guard = setup_default_logger();
+
+    test_screenshot(
+        "blargg_cpu_instrs",
+        "../external/test_roms/blargg/cpu_instrs/cpu_instrs.gb",
+        "tests/reference/blargg/cpu_instrs.png",
+        BLARGG_FRAMES,
+    );
+}
+
+#[test]
+fn test_blargg_instr_timing_screen() {
+    let _guard = setup_default_logger();
+
+    test_screenshot(
+        "blargg_instr_timing",
+        "../external/test_roms/blargg/instr_timing/instr_timing.gb",
+        "tests/reference/blargg/instr_timing.png",
+        BLARGG_FRAMES,
+    );
+}
@@ -0,0 +1,65 @@
+mod helpers;
+
+use gbemu_rust_lib::prelude::*;
+use helpers::setup_default_logger;
+
+fn print_rom(message: &[u8]) -> Vec<u8> {
+    let instructions = [
+        0x21, 0x11, 0x01, // LD HL, message
+        0x2A, // LD A, (HL+)
+        0xB7, // OR A
+        0x28, 0x08, // JR Z, +8
+        0xE0, 0x01, // LDH (SB), A
+        0x3E, 0x81, // LD A, 0x81
+        0xE0, 0x02, // LDH (SC), A
+        0x18, 0xF4, // JR -12
+        0x18, 0xFE, // JR -2
+    ];
+    let mut rom_buffer = vec![0; 32 * 1024];
+    rom_buffer[0x0100..0x0100 + instructions.len()].copy_from_slice(&instructions);
+    rom_buffer[0x0111..0x0111 + message.len()].copy_from_slice(message);
+
+    rom_buffer
+}
+
+#[test]
+fn test_await_pattern() {
+    let _guard = setup_default_logger();
+
+    let serial = CaptureSerial::new();
+    let capture = serial.capture();
+    let mut emu = Emulator::new_from_buffer(
+        print_rom(b"Passed\n\0"),
+        false,
+        None,
+        Some(Box::new(serial)),
+    )
+    .unwrap();
+
+    let cycle = capture.await_pattern(&mut emu, "Passed\n", 10_000).unwrap();
+
+    assert_eq!(capture.transcript(), "Passed\n");
+    assert_eq!(capture.bytes().last().unwrap().cycle, cycle);
+    assert_eq!(emu.system.io.serial.get_last_buffer(), "Passed");
+}
+
+#[test]
+fn test_await_pattern_timeout() {
+    let _guard = setup_default_logger();
+
+    let serial = CaptureSerial::new();
+    let capture = serial.capture();
+    let mut emu = Emulator::new_from_buffer(
+        print_rom(b"Passed\n\0"),
+        false,
+        None,
+        Some(Box::new(serial)),
+    )
+    .unwrap();
+
+    assert!(matches!(
+        capture.await_pattern(&mut emu, "Failed", 1_000),
+        Err(SerialAwaitError::Timeout { cycles: 1_000 })
+    ));
+    assert_eq!(capture.cycle(), 1_000);
+}
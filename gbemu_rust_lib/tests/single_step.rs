@@ -0,0 +1,67 @@
+mod helpers;
+
+use helpers::setup_default_logger;
+use helpers::single_step::{run_test_vector, test_single_step_dir};
+
+fn vector(json: &str) -> serde_json::Value {
+    serde_json::from_str(json).unwrap()
+}
+
+#[test]
+fn test_harness_nop() {
+    let _guard = setup_default_logger();
+
+    let test = vector(
+        r#"{
+            "name": "00 0000",
+            "initial": {
+                "a": 1, "b": 2, "c": 3, "d": 4, "e": 5, "f": 176, "h": 6, "l": 7,
+                "pc": 257, "sp": 65534, "ime": 0, "ie": 0,
+                "ram": [[256, 0], [257, 0]]
+            },
+            "final": {
+                "a": 1, "b": 2, "c": 3, "d": 4, "e": 5, "f": 176, "h": 6, "l": 7,
+                "pc": 258, "sp": 65534, "ime": 0,
+                "ram": [[256, 0], [257, 0]]
+            },
+            "cycles": [[257, 0, "r-m"]]
+        }"#,
+    );
+
+    assert_eq!(run_test_vector(&test), Ok(()));
+}
+
+#[test]
+fn test_harness_inc_ind_hl() {
+    let _guard = setup_default_logger();
+
+    let test = vector(
+        r#"{
+            "name": "34 0000",
+            "initial": {
+                "a": 0, "b": 0, "c": 0, "d": 0, "e": 0, "f": 0, "h": 192, "l": 0,
+                "pc": 257, "sp": 65534, "ime": 0, "ie": 0,
+                "ram": [[256, 52], [257, 0], [49152, 15]]
+            },
+            "final": {
+                "a": 0, "b": 0, "c": 0, "d": 0, "e": 0, "f": 32, "h": 192, "l": 0,
+                "pc": 258, "sp": 65534, "ime": 0,
+                "ram": [[256, 52], [257, 0], [49152, 16]]
+            },
+            "cycles": [[49152, 15, "r-m"], [49152, 16, "-wm"], [257, 0, "r-m"]]
+        }"#,
+    );
+    assert_eq!(run_test_vector(&test), Ok(()));
+
+    // the write happens in the second M-cycle, not the third one
+    let mut test = test;
+    test["cycles"] = vector(r#"[[49152, 15, "r-m"], [null, null, "---"], [49152, 16, "-wm"]]"#);
+    assert!(run_test_vector(&test).is_err());
+}
+
+#[test]
+fn test_sm83() {
+    let _guard = setup_default_logger();
+
+    test_single_step_dir("../external/sm83/v1");
+}